@@ -0,0 +1,59 @@
+use anchor_lang::error_code;
+
+#[error_code]
+pub enum VaultError {
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Overflow")]
+    Overflow,
+    #[msg("A token vault is still open")]
+    TokenVaultsNotEmpty,
+    #[msg("The vault is locked")]
+    VaultLocked,
+    #[msg("The lock can only be extended")]
+    LockNotExtended,
+    #[msg("Withdrawal limit exceeded for the current window")]
+    WithdrawLimitExceeded,
+    #[msg("Too many owners")]
+    TooManyOwners,
+    #[msg("Threshold must be between 1 and the number of owners")]
+    InvalidThreshold,
+    #[msg("Duplicate owner")]
+    DuplicateOwner,
+    #[msg("Multisig vaults can only be withdrawn from through a proposal")]
+    MultisigVault,
+    #[msg("Not a multisig vault")]
+    NotMultisig,
+    #[msg("Signer is not an owner of the vault")]
+    NotOwner,
+    #[msg("Proposal already approved by this owner")]
+    AlreadyApproved,
+    #[msg("Not enough approvals")]
+    ThresholdNotReached,
+    #[msg("Proposal expired")]
+    ProposalExpired,
+    #[msg("Proposal has not expired yet")]
+    ProposalNotExpired,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Delegation expired")]
+    DelegateExpired,
+    #[msg("Amount exceeds the delegated allowance")]
+    AllowanceExceeded,
+    #[msg("Signer is not the guardian of the vault")]
+    NotGuardian,
+    #[msg("Inactivity period must be positive")]
+    InvalidInactivityPeriod,
+    #[msg("No recovery in progress or inactivity period not elapsed")]
+    RecoveryNotReady,
+    #[msg("Source and destination vaults must differ")]
+    SameVault,
+    #[msg("Signer is not the proposed owner")]
+    NotPendingOwner,
+    #[msg("The vault still has lamports staked")]
+    StakeActive,
+    #[msg("Too many token vaults")]
+    TooManyTokenVaults,
+    #[msg("Revoke every delegate first")]
+    DelegatesOutstanding,
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct VaultInitialized {
+    pub vault_state: Pubkey,
+    pub owner: Pubkey,
+    pub vault_id: u64,
+}
+
+#[event]
+pub struct Deposited {
+    pub vault_state: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub vault_state: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub vault_state: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64, // lamports swept out of the vault
+}
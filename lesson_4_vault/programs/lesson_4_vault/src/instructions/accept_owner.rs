@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.pending_owner == Some(new_owner.key()) @ VaultError::NotPendingOwner,
+        // allowances granted by the old owner must not carry over
+        constraint = vault_state.delegates == 0 @ VaultError::DelegatesOutstanding,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> AcceptOwner<'info> {
+    pub fn accept_owner(&mut self) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        self.vault_state.owner = self.new_owner.key();
+        self.vault_state.pending_owner = None;
+
+        // so is the old owner's recovery setup: the new owner picks their own guardian
+        self.vault_state.guardian = None;
+        self.vault_state.inactivity_period = 0;
+        self.vault_state.recovery_address = Pubkey::default();
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, Delegate, VaultState};
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct ApproveDelegate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    // approving again replaces the previous allowance
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"delegate", vault_state.key().as_ref(), delegate.as_ref()],
+        bump,
+        space = Delegate::DISCRIMINATOR.len() + Delegate::INIT_SPACE,
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    pub destination: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ApproveDelegate<'info> {
+    pub fn approve_delegate(
+        &mut self,
+        delegate: Pubkey,
+        amount: u64,
+        expires_at: i64,
+        bumps: &ApproveDelegateBumps,
+    ) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        require!(amount > 0, VaultError::InvalidAmount);
+        require!(
+            expires_at == 0 || expires_at > Clock::get()?.unix_timestamp,
+            VaultError::InvalidExpiry
+        );
+
+        // a fresh account, not a replaced allowance
+        if self.delegate_account.vault_state == Pubkey::default() {
+            self.vault_state.delegates = self.vault_state.delegates.checked_add(1).ok_or(VaultError::Overflow)?;
+        }
+
+        self.delegate_account.set_inner(Delegate {
+            vault_state: self.vault_state.key(),
+            delegate,
+            destination: self.destination.key(),
+            allowance: amount,
+            expires_at,
+            bump: bumps.delegate_account,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState, WithdrawProposal};
+
+#[derive(Accounts)]
+pub struct ApproveWithdraw<'info> {
+    pub owner: Signer<'info>,
+
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        seeds = [b"proposal", vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, WithdrawProposal>,
+}
+
+impl<'info> ApproveWithdraw<'info> {
+    pub fn approve_withdraw(&mut self) -> Result<()> {
+        let index = self.vault_state.owner_index(self.owner.key).ok_or(VaultError::NotOwner)?;
+        require!(!self.proposal.is_expired(Clock::get()?.unix_timestamp), VaultError::ProposalExpired);
+        require!(self.proposal.approvals & (1 << index) == 0, VaultError::AlreadyApproved);
+
+        self.proposal.approvals |= 1 << index;
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState, WithdrawProposal};
+
+#[derive(Accounts)]
+pub struct CancelWithdraw<'info> {
+    pub signer: Signer<'info>,
+
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = vault_state,
+        has_one = proposer,
+        seeds = [b"proposal", vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, WithdrawProposal>,
+
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+}
+
+impl<'info> CancelWithdraw<'info> {
+    pub fn cancel_withdraw(&mut self) -> Result<()> {
+        // the proposer can cancel at any time, other owners only clean up expired proposals
+        if self.signer.key() != self.proposal.proposer {
+            require!(self.vault_state.owner_index(self.signer.key).is_some(), VaultError::NotOwner);
+            require!(
+                self.proposal.is_expired(Clock::get()?.unix_timestamp),
+                VaultError::ProposalNotExpired
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{errors::VaultError, events::*, VaultState};
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> Close<'info> {
+    pub fn close(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.vault_state.is_locked(now), VaultError::VaultLocked);
+
+        // token vaults are owned by vault_state: closing it would lock their tokens forever
+        require!(self.vault_state.token_mints.is_empty(), VaultError::TokenVaultsNotEmpty);
+
+        // delegate PDAs are seeded from vault_state: a vault re-created at the same address would revive them
+        require!(self.vault_state.delegates == 0, VaultError::DelegatesOutstanding);
+
+        // the stake account's withdraw authority is the vault PDA: closing would orphan it
+        require!(self.vault_state.staked == 0, VaultError::StakeActive);
+
+        // closing must not be a way around the withdrawal limit: only the rent deposited at initialize is free
+        let rent_exempt = Rent::get()?.minimum_balance(self.vault.to_account_info().data_len());
+        self.vault_state.consume_allowance(self.vault.lamports().saturating_sub(rent_exempt), now)?;
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.user.to_account_info(),
+        };
+        
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            self.vault_state.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump]
+        ]];
+
+        let amount = self.vault.to_account_info().lamports();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        emit!(VaultClosed {
+            vault_state: self.vault_state.key(),
+            owner: self.user.key(),
+            amount,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, close_account, CloseAccount}};
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct CloseSpl<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_state,
+        associated_token::token_program = token_program,
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseSpl<'info> {
+    pub fn close_spl(&mut self) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        require!(!self.vault_state.is_locked(Clock::get()?.unix_timestamp), VaultError::VaultLocked);
+
+        let creator = self.vault_state.creator;
+        let vault_id = self.vault_state.vault_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"state",
+            creator.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_state.state_bump]
+        ]];
+
+        if self.token_vault.amount > 0 {
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_accounts = TransferChecked {
+                from: self.token_vault.to_account_info(),
+                to: self.user_ata.to_account_info(),
+                authority: self.vault_state.to_account_info(),
+                mint: self.mint.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            transfer_checked(cpi_ctx, self.token_vault.amount, self.mint.decimals)?;
+        }
+        self.vault_state.untrack_token_vault(&self.mint.key());
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.token_vault.to_account_info(),
+            destination: self.user.to_account_info(),
+            authority: self.vault_state.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{errors::VaultError, events::*, VaultState};
+
+#[derive(Accounts)]
+pub struct CompleteRecovery<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_state.guardian == Some(guardian.key()) @ VaultError::NotGuardian,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = vault_state.recovery_address,
+    )]
+    pub recovery_address: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CompleteRecovery<'info> {
+    pub fn complete_recovery(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(self.vault_state.is_recovery_ready(now), VaultError::RecoveryNotReady);
+        require!(!self.vault_state.is_locked(now), VaultError::VaultLocked);
+
+        self.vault_state.recovery_started_at = 0;
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.recovery_address.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            self.vault_state.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump]
+        ]];
+
+        let amount = self.vault.lamports();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        self.vault_state.record_withdrawal(amount)?;
+        emit!(Withdrawn {
+            vault_state: self.vault_state.key(),
+            destination: self.recovery_address.key(),
+            amount,
+            total_withdrawn: self.vault_state.total_withdrawn,
+        });
+        Ok(())
+    }
+}
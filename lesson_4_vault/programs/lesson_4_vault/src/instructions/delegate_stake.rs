@@ -0,0 +1,133 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, sysvar::stake_history},
+    system_program::{allocate, assign, transfer, Allocate, Assign, Transfer},
+};
+use solana_stake_interface::{
+    self as stake,
+    instruction as stake_instruction,
+    state::{Authorized, Lockup, StakeStateV2},
+};
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct DelegateStake<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    // -> pda (derived from seeds), created here and handed over to the stake program
+    // -> staker and withdrawer : the vault pda
+    #[account(
+        mut,
+        seeds = [b"stake", vault_state.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: SystemAccount<'info>,
+
+    /// CHECK: validated by the stake program on delegation
+    pub vote_account: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = stake::config::ID)]
+    pub stake_config: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DelegateStake<'info> {
+    pub fn delegate_stake(&mut self, amount: u64, bumps: &DelegateStakeBumps) -> Result<()> {
+        // one stake account per vault, so the current one has to be withdrawn first
+        require!(self.vault_state.staked == 0, VaultError::StakeActive);
+        self.vault_state.record_owner_activity();
+
+        require!(amount > 0, VaultError::InvalidAmount);
+
+        // the rent reserve comes out of the vault too and is returned by withdraw_stake
+        let lamports = amount
+            .checked_add(self.rent.minimum_balance(StakeStateV2::size_of()))
+            .ok_or(VaultError::Overflow)?;
+
+        let vault_state_key = self.vault_state.key();
+        let vault_seeds: &[&[u8]] = &[b"vault", vault_state_key.as_ref(), &[self.vault_state.vault_bump]];
+        let stake_seeds: &[&[u8]] = &[b"stake", vault_state_key.as_ref(), &[bumps.stake_account]];
+
+        // not create_account: it fails once the address holds any lamports, and anyone can send some there.
+        // funding, allocating and assigning separately works either way, a gift simply gets staked too
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.stake_account.to_account_info(),
+        };
+        transfer(CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, &[vault_seeds]), lamports)?;
+
+        let cpi_accounts = Allocate {
+            account_to_allocate: self.stake_account.to_account_info(),
+        };
+        allocate(
+            CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, &[stake_seeds]),
+            StakeStateV2::size_of() as u64,
+        )?;
+
+        let cpi_accounts = Assign {
+            account_to_assign: self.stake_account.to_account_info(),
+        };
+        assign(CpiContext::new_with_signer(cpi_program, cpi_accounts, &[stake_seeds]), &stake::program::ID)?;
+
+        let authorized = Authorized {
+            staker: self.vault.key(),
+            withdrawer: self.vault.key(),
+        };
+        invoke_signed(
+            &stake_instruction::initialize(self.stake_account.key, &authorized, &Lockup::default()),
+            &[
+                self.stake_account.to_account_info(),
+                self.rent.to_account_info(),
+                self.stake_program.to_account_info(),
+            ],
+            &[],
+        )?;
+
+        invoke_signed(
+            &stake_instruction::delegate_stake(self.stake_account.key, self.vault.key, self.vote_account.key),
+            &[
+                self.stake_account.to_account_info(),
+                self.vote_account.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_history.to_account_info(),
+                self.stake_config.to_account_info(),
+                self.vault.to_account_info(),
+                self.stake_program.to_account_info(),
+            ],
+            &[vault_seeds],
+        )?;
+
+        self.vault_state.staked = self.stake_account.lamports();
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{errors::VaultError, events::*, Delegate, VaultState};
+
+#[derive(Accounts)]
+pub struct DelegateWithdraw<'info> {
+    pub delegate: Signer<'info>,
+
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = vault_state,
+        has_one = delegate,
+        has_one = destination,
+        seeds = [b"delegate", vault_state.key().as_ref(), delegate.key().as_ref()],
+        bump = delegate_account.bump,
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DelegateWithdraw<'info> {
+    pub fn delegate_withdraw(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.delegate_account.is_expired(now), VaultError::DelegateExpired);
+        require!(amount <= self.delegate_account.allowance, VaultError::AllowanceExceeded);
+
+        // the vault's own lock and withdrawal limit apply to delegates as well
+        require!(!self.vault_state.is_locked(now), VaultError::VaultLocked);
+        self.vault_state.consume_allowance(amount, now)?;
+
+        self.delegate_account.allowance -= amount;
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.destination.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            self.vault_state.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump]
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        self.vault_state.record_withdrawal(amount)?;
+        emit!(Withdrawn {
+            vault_state: self.vault_state.key(),
+            destination: self.destination.key(),
+            amount,
+            total_withdrawn: self.vault_state.total_withdrawn,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{errors::VaultError, events::*, VaultState, WithdrawProposal};
+
+#[derive(Accounts)]
+pub struct ExecuteWithdraw<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = vault_state,
+        has_one = proposer,
+        has_one = recipient,
+        seeds = [b"proposal", vault_state.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, WithdrawProposal>,
+
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExecuteWithdraw<'info> {
+    pub fn execute_withdraw(&mut self) -> Result<()> {
+        require!(self.vault_state.owner_index(self.owner.key).is_some(), VaultError::NotOwner);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.proposal.is_expired(now), VaultError::ProposalExpired);
+        require!(
+            self.proposal.approval_count() >= self.vault_state.threshold,
+            VaultError::ThresholdNotReached
+        );
+        require!(!self.vault_state.is_locked(now), VaultError::VaultLocked);
+        self.vault_state.consume_allowance(self.proposal.amount, now)?;
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.recipient.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            self.vault_state.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump]
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, self.proposal.amount)?;
+
+        self.vault_state.record_withdrawal(self.proposal.amount)?;
+        emit!(Withdrawn {
+            vault_state: self.vault_state.key(),
+            destination: self.recipient.key(),
+            amount: self.proposal.amount,
+            total_withdrawn: self.vault_state.total_withdrawn,
+        });
+        Ok(())
+    }
+}
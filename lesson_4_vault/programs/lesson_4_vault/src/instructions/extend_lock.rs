@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> ExtendLock<'info> {
+    pub fn extend_lock(&mut self, unlock_at: i64) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        // a savings lock that can be shortened is not a lock
+        require!(unlock_at > self.vault_state.unlock_at, VaultError::LockNotExtended);
+
+        self.vault_state.unlock_at = unlock_at;
+        Ok(())
+    }
+}
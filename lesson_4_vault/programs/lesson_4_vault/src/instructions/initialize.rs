@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{events::*, VaultState};
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct Initialize<'info> {
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // -> pda (derived from seeds)
+    // -> owned by my program : the program can change the data + amount of lamports
+    // -> authority : my program
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"state", user.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+        space = VaultState::DISCRIMINATOR.len() + VaultState::INIT_SPACE,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    // -> pda (derived from seeds)
+    // -> owned by system program : the system program could change the data + amount of lamports
+    // -> authority : my program
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>, // owner system program + no data
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> Initialize<'info> {
+    pub fn initialize(&mut self, vault_id: u64, unlock_at: i64, bump: &InitializeBumps) -> Result<()> {
+        let rent_exempt: u64 = Rent::get()?.minimum_balance(self.vault.to_account_info().data_len());
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.user.to_account_info(),
+            to: self.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        transfer(cpi_ctx, rent_exempt)?;
+
+        self.vault_state.creator = self.user.key();
+        self.vault_state.vault_id = vault_id;
+        self.vault_state.owner = self.user.key();
+        self.vault_state.vault_bump = bump.vault;
+        self.vault_state.state_bump = bump.vault_state;
+        self.vault_state.unlock_at = unlock_at;
+
+        emit!(VaultInitialized {
+            vault_state: self.vault_state.key(),
+            owner: self.user.key(),
+            vault_id,
+        });
+        Ok(())
+    }
+
+    pub fn set_owners(&mut self, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        self.vault_state.set_owners(owners, threshold)
+    }
+}
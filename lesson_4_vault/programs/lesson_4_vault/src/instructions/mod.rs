@@ -0,0 +1,51 @@
+pub mod initialize;
+pub mod deposit;
+pub mod withdraw;
+pub mod close;
+pub mod deposit_spl;
+pub mod withdraw_spl;
+pub mod close_spl;
+pub mod extend_lock;
+pub mod set_withdraw_limit;
+pub mod propose_withdraw;
+pub mod approve_withdraw;
+pub mod execute_withdraw;
+pub mod cancel_withdraw;
+pub mod approve_delegate;
+pub mod revoke_delegate;
+pub mod delegate_withdraw;
+pub mod set_guardian;
+pub mod start_recovery;
+pub mod complete_recovery;
+pub mod transfer_between_vaults;
+pub mod propose_owner;
+pub mod accept_owner;
+pub mod delegate_stake;
+pub mod deactivate_stake;
+pub mod withdraw_stake;
+
+pub use initialize::*;
+pub use deposit::*;
+pub use withdraw::*;
+pub use close::*;
+pub use deposit_spl::*;
+pub use withdraw_spl::*;
+pub use close_spl::*;
+pub use extend_lock::*;
+pub use set_withdraw_limit::*;
+pub use propose_withdraw::*;
+pub use approve_withdraw::*;
+pub use execute_withdraw::*;
+pub use cancel_withdraw::*;
+pub use approve_delegate::*;
+pub use revoke_delegate::*;
+pub use delegate_withdraw::*;
+pub use set_guardian::*;
+pub use start_recovery::*;
+pub use complete_recovery::*;
+pub use transfer_between_vaults::*;
+pub use propose_owner::*;
+pub use accept_owner::*;
+pub use delegate_stake::*;
+pub use deactivate_stake::*;
+pub use withdraw_stake::*;
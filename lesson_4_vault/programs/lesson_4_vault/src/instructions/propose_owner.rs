@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct ProposeOwner<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> ProposeOwner<'info> {
+    pub fn propose_owner(&mut self, new_owner: Pubkey) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        // nothing changes until new_owner signs accept_owner, so a typo cannot lose the vault;
+        // proposing the default key withdraws a pending proposal
+        self.vault_state.pending_owner = (new_owner != Pubkey::default()).then_some(new_owner);
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState, WithdrawProposal};
+
+#[derive(Accounts)]
+pub struct ProposeWithdraw<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_state.is_multisig() @ VaultError::NotMultisig,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = proposer,
+        seeds = [b"proposal", vault_state.key().as_ref(), vault_state.proposal_count.to_le_bytes().as_ref()],
+        bump,
+        space = WithdrawProposal::DISCRIMINATOR.len() + WithdrawProposal::INIT_SPACE,
+    )]
+    pub proposal: Account<'info, WithdrawProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ProposeWithdraw<'info> {
+    pub fn propose_withdraw(
+        &mut self,
+        recipient: Pubkey,
+        amount: u64,
+        expires_at: i64,
+        bumps: &ProposeWithdrawBumps,
+    ) -> Result<()> {
+        let index = self.vault_state.owner_index(self.proposer.key).ok_or(VaultError::NotOwner)?;
+        require!(amount > 0, VaultError::InvalidAmount);
+        require!(expires_at > Clock::get()?.unix_timestamp, VaultError::InvalidExpiry);
+
+        // proposing counts as the proposer's approval
+        self.proposal.set_inner(WithdrawProposal {
+            vault_state: self.vault_state.key(),
+            id: self.vault_state.proposal_count,
+            proposer: self.proposer.key(),
+            recipient,
+            amount,
+            approvals: 1 << index,
+            expires_at,
+            bump: bumps.proposal,
+        });
+
+        self.vault_state.proposal_count = self.vault_state.proposal_count
+            .checked_add(1)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+}
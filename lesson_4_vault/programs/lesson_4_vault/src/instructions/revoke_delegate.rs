@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, Delegate, VaultState};
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = vault_state,
+        seeds = [b"delegate", vault_state.key().as_ref(), delegate_account.delegate.as_ref()],
+        bump = delegate_account.bump,
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+}
+
+impl<'info> RevokeDelegate<'info> {
+    pub fn revoke_delegate(&mut self) -> Result<()> {
+        self.vault_state.record_owner_activity();
+        self.vault_state.delegates = self.vault_state.delegates.saturating_sub(1);
+        Ok(())
+    }
+}
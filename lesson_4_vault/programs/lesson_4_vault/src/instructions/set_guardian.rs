@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> SetGuardian<'info> {
+    pub fn set_guardian(&mut self, guardian: Option<Pubkey>, inactivity_period: i64) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        if guardian.is_some() {
+            require!(inactivity_period > 0, VaultError::InvalidInactivityPeriod);
+        }
+
+        self.vault_state.guardian = guardian;
+        self.vault_state.inactivity_period = inactivity_period;
+        Ok(())
+    }
+}
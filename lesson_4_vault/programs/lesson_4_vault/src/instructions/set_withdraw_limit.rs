@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct SetWithdrawLimit<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> SetWithdrawLimit<'info> {
+    pub fn set_withdraw_limit(&mut self, limit: u64) -> Result<()> {
+        self.vault_state.record_owner_activity();
+        self.vault_state.set_withdraw_limit(limit, Clock::get()?.unix_timestamp)
+    }
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct StartRecovery<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_state.guardian == Some(guardian.key()) @ VaultError::NotGuardian,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+impl<'info> StartRecovery<'info> {
+    pub fn start_recovery(&mut self, recovery_address: Pubkey) -> Result<()> {
+        // restarting moves the challenge window, so the owner always gets a full inactivity period
+        self.vault_state.recovery_started_at = Clock::get()?.unix_timestamp;
+        self.vault_state.recovery_address = recovery_address;
+        Ok(())
+    }
+}
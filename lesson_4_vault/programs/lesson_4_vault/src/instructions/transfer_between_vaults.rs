@@ -0,0 +1,84 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{errors::VaultError, events::*, VaultState};
+
+#[derive(Accounts)]
+pub struct TransferBetweenVaults<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", from_state.creator.as_ref(), from_state.vault_id.to_le_bytes().as_ref()],
+        bump = from_state.state_bump,
+        constraint = from_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !from_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub from_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", from_state.key().as_ref()],
+        bump = from_state.vault_bump,
+    )]
+    pub from_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", to_state.creator.as_ref(), to_state.vault_id.to_le_bytes().as_ref()],
+        bump = to_state.state_bump,
+        constraint = to_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = to_state.key() != from_state.key() @ VaultError::SameVault,
+    )]
+    pub to_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", to_state.key().as_ref()],
+        bump = to_state.vault_bump,
+    )]
+    pub to_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TransferBetweenVaults<'info> {
+    pub fn transfer_between_vaults(&mut self, amount: u64) -> Result<()> {
+        self.from_state.record_owner_activity();
+        self.to_state.record_owner_activity();
+
+        // leaving the source vault is a withdrawal as far as its lock and limit are concerned
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.from_state.is_locked(now), VaultError::VaultLocked);
+        self.from_state.consume_allowance(amount, now)?;
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.from_vault.to_account_info(),
+            to: self.to_vault.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault",
+            self.from_state.to_account_info().key.as_ref(),
+            &[self.from_state.vault_bump]
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        self.from_state.record_withdrawal(amount)?;
+        self.to_state.record_deposit(amount)?;
+        emit!(Withdrawn {
+            vault_state: self.from_state.key(),
+            destination: self.to_vault.key(),
+            amount,
+            total_withdrawn: self.from_state.total_withdrawn,
+        });
+        emit!(Deposited {
+            vault_state: self.to_state.key(),
+            amount,
+            total_deposited: self.to_state.total_deposited,
+        });
+        Ok(())
+    }
+}
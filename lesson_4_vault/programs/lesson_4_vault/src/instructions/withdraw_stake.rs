@@ -0,0 +1,71 @@
+use anchor_lang::{prelude::*, solana_program::{program::invoke_signed, sysvar::stake_history}};
+use solana_stake_interface::{self as stake, instruction as stake_instruction};
+
+use crate::{errors::VaultError, VaultState};
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), vault_state.vault_id.to_le_bytes().as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.owner == user.key() @ VaultError::NotOwner,
+        constraint = !vault_state.is_multisig() @ VaultError::MultisigVault,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: pda checked, state checked by the stake program
+    #[account(
+        mut,
+        seeds = [b"stake", vault_state.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> WithdrawStake<'info> {
+    pub fn withdraw_stake(&mut self) -> Result<()> {
+        self.vault_state.record_owner_activity();
+
+        let vault_state_key = self.vault_state.key();
+        let vault_seeds: &[&[u8]] = &[b"vault", vault_state_key.as_ref(), &[self.vault_state.vault_bump]];
+
+        // everything goes back to the vault, rewards and rent reserve included;
+        // the stake program rejects this until the stake is fully deactivated
+        let amount = self.stake_account.lamports();
+        invoke_signed(
+            &stake_instruction::withdraw(self.stake_account.key, self.vault.key, self.vault.key, amount, None),
+            &[
+                self.stake_account.to_account_info(),
+                self.vault.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_history.to_account_info(),
+                self.vault.to_account_info(),
+                self.stake_program.to_account_info(),
+            ],
+            &[vault_seeds],
+        )?;
+
+        self.vault_state.staked = 0;
+        Ok(())
+    }
+}
@@ -1,4 +1,12 @@
-use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_lang::prelude::*;
+
+pub mod errors;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+pub use instructions::*;
+pub use state::*;
 
 declare_id!("9mD6HSyKeX673CZowppfX7EazqrhomGGCLdzSm846Gn6");
 
@@ -6,8 +14,19 @@ declare_id!("9mD6HSyKeX673CZowppfX7EazqrhomGGCLdzSm846Gn6");
 pub mod lesson_4_vault {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        ctx.accounts.initialize(&ctx.bumps)
+    pub fn initialize(ctx: Context<Initialize>, vault_id: u64, unlock_at: i64) -> Result<()> {
+        ctx.accounts.initialize(vault_id, unlock_at, &ctx.bumps)
+    }
+
+    pub fn initialize_multisig(
+        ctx: Context<Initialize>,
+        vault_id: u64,
+        unlock_at: i64,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.initialize(vault_id, unlock_at, &ctx.bumps)?;
+        ctx.accounts.set_owners(owners, threshold)
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
@@ -21,172 +40,102 @@ pub mod lesson_4_vault {
     pub fn close(ctx: Context<Close>) -> Result<()> {
         ctx.accounts.close()
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    // -> pda (derived from seeds)
-    // -> owned by my program : the program can change the data + amount of lamports
-    // -> authority : my program
-    #[account(
-        init,
-        payer = user,
-        seeds = [b"state", user.key().as_ref()],
-        bump,
-        space = VaultState::DISCRIMINATOR.len() + VaultState::INIT_SPACE,
-    )]
-    pub vault_state: Account<'info, VaultState>,
-
-    // -> pda (derived from seeds)
-    // -> owned by system program : the system program could change the data + amount of lamports
-    // -> authority : my program
-    #[account(
-        mut,
-        seeds = [b"vault", vault_state.key().as_ref()],
-        bump,
-    )]
-    pub vault: SystemAccount<'info>, // owner system program + no data
-    pub system_program: Program<'info, System>
-}
+    pub fn deposit_spl(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
+        ctx.accounts.deposit_spl(amount)
+    }
 
-impl<'info> Initialize<'info> {
-    pub fn initialize(&mut self, bump: &InitializeBumps) -> Result<()> {
-        let rent_exempt: u64 = Rent::get()?.minimum_balance(self.vault.to_account_info().data_len());
+    pub fn withdraw_spl(ctx: Context<WithdrawSpl>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_spl(amount)
+    }
 
-        let cpi_program = self.system_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: self.user.to_account_info(),
-            to: self.vault.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer(cpi_ctx, rent_exempt)?;
+    pub fn close_spl(ctx: Context<CloseSpl>) -> Result<()> {
+        ctx.accounts.close_spl()
+    }
 
-        self.vault_state.vault_bump = bump.vault;
-        self.vault_state.state_bump = bump.vault_state;
-        Ok(())
+    pub fn extend_lock(ctx: Context<ExtendLock>, unlock_at: i64) -> Result<()> {
+        ctx.accounts.extend_lock(unlock_at)
     }
-}
 
-#[derive(InitSpace)]
-#[account]
-pub struct VaultState {
-    pub vault_bump: u8,
-    pub state_bump: u8,
-}
+    pub fn set_withdraw_limit(ctx: Context<SetWithdrawLimit>, limit: u64) -> Result<()> {
+        ctx.accounts.set_withdraw_limit(limit)
+    }
 
+    pub fn propose_withdraw(
+        ctx: Context<ProposeWithdraw>,
+        recipient: Pubkey,
+        amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.propose_withdraw(recipient, amount, expires_at, &ctx.bumps)
+    }
 
-#[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    #[account(
-        seeds = [b"state", user.key().as_ref()],
-        bump = vault_state.state_bump,
-    )]
-    pub vault_state: Account<'info, VaultState>,
-
-    #[account(
-        mut,
-        seeds = [b"vault", vault_state.key().as_ref()],
-        bump = vault_state.vault_bump,
-    )]
-    pub vault: SystemAccount<'info>,
-    pub system_program: Program<'info, System>
-}
+    pub fn approve_withdraw(ctx: Context<ApproveWithdraw>) -> Result<()> {
+        ctx.accounts.approve_withdraw()
+    }
 
-impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self, amount: u64) -> Result<()> {
-        let cpi_program = self.system_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: self.user.to_account_info(),
-            to: self.vault.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer(cpi_ctx, amount)
+    pub fn execute_withdraw(ctx: Context<ExecuteWithdraw>) -> Result<()> {
+        ctx.accounts.execute_withdraw()
     }
-}
 
-#[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    #[account(
-        seeds = [b"state", user.key().as_ref()],
-        bump = vault_state.state_bump,
-    )]
-    pub vault_state: Account<'info, VaultState>,
-
-    #[account(
-        mut,
-        seeds = [b"vault", vault_state.key().as_ref()],
-        bump = vault_state.vault_bump,
-    )]
-    pub vault: SystemAccount<'info>,
-    pub system_program: Program<'info, System>
-}
+    pub fn cancel_withdraw(ctx: Context<CancelWithdraw>) -> Result<()> {
+        ctx.accounts.cancel_withdraw()
+    }
 
-impl<'info> Withdraw<'info> {
-    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
-        let cpi_program = self.system_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: self.vault.to_account_info(),
-            to: self.user.to_account_info(),
-        };
-        
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"vault",
-            self.vault_state.to_account_info().key.as_ref(),
-            &[self.vault_state.vault_bump]
-        ]];
-
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer(cpi_ctx, amount)
+    pub fn approve_delegate(
+        ctx: Context<ApproveDelegate>,
+        delegate: Pubkey,
+        amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.approve_delegate(delegate, amount, expires_at, &ctx.bumps)
     }
-}
 
-#[derive(Accounts)]
-pub struct Close<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    #[account(
-        mut,
-        close = user,
-        seeds = [b"state", user.key().as_ref()],
-        bump = vault_state.state_bump,
-    )]
-    pub vault_state: Account<'info, VaultState>,
-
-    #[account(
-        mut,
-        seeds = [b"vault", vault_state.key().as_ref()],
-        bump = vault_state.vault_bump,
-    )]
-    pub vault: SystemAccount<'info>,
-    pub system_program: Program<'info, System>
-}
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        ctx.accounts.revoke_delegate()
+    }
+
+    pub fn delegate_withdraw(ctx: Context<DelegateWithdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.delegate_withdraw(amount)
+    }
+
+    pub fn set_guardian(
+        ctx: Context<SetGuardian>,
+        guardian: Option<Pubkey>,
+        inactivity_period: i64,
+    ) -> Result<()> {
+        ctx.accounts.set_guardian(guardian, inactivity_period)
+    }
+
+    pub fn start_recovery(ctx: Context<StartRecovery>, recovery_address: Pubkey) -> Result<()> {
+        ctx.accounts.start_recovery(recovery_address)
+    }
+
+    pub fn complete_recovery(ctx: Context<CompleteRecovery>) -> Result<()> {
+        ctx.accounts.complete_recovery()
+    }
+
+    pub fn transfer_between_vaults(ctx: Context<TransferBetweenVaults>, amount: u64) -> Result<()> {
+        ctx.accounts.transfer_between_vaults(amount)
+    }
+
+    pub fn propose_owner(ctx: Context<ProposeOwner>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.propose_owner(new_owner)
+    }
+
+    pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+        ctx.accounts.accept_owner()
+    }
+
+    pub fn delegate_stake(ctx: Context<DelegateStake>, amount: u64) -> Result<()> {
+        ctx.accounts.delegate_stake(amount, &ctx.bumps)
+    }
+
+    pub fn deactivate_stake(ctx: Context<DeactivateStake>) -> Result<()> {
+        ctx.accounts.deactivate_stake()
+    }
 
-impl<'info> Close<'info> {
-    pub fn close(&mut self) -> Result<()> {
-        let cpi_program = self.system_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: self.vault.to_account_info(),
-            to: self.user.to_account_info(),
-        };
-        
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"vault",
-            self.vault_state.to_account_info().key.as_ref(),
-            &[self.vault_state.vault_bump]
-        ]];
-
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer(cpi_ctx, self.vault.to_account_info().lamports())
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        ctx.accounts.withdraw_stake()
     }
 }
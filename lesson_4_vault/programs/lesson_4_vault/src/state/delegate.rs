@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Delegate {
+    pub vault_state: Pubkey,
+    pub delegate: Pubkey,
+    pub destination: Pubkey, // chosen by the owner, the delegate can only pay out there
+    pub allowance: u64,      // lamports left to withdraw
+    pub expires_at: i64,     // 0 = never
+    pub bump: u8,
+}
+
+impl Delegate {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
@@ -0,0 +1,8 @@
+pub mod vault_state;
+pub use vault_state::*;
+
+pub mod withdraw_proposal;
+pub use withdraw_proposal::*;
+
+pub mod delegate;
+pub use delegate::*;
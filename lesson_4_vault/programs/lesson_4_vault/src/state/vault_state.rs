@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+
+pub const WITHDRAW_WINDOW: i64 = 24 * 60 * 60;
+pub const LIMIT_INCREASE_DELAY: i64 = 24 * 60 * 60;
+pub const MAX_OWNERS: usize = 10;
+pub const MAX_TOKEN_VAULTS: usize = 8;
+
+#[derive(InitSpace)]
+#[account]
+pub struct VaultState {
+    pub creator: Pubkey,             // with vault_id, the stable seed of the vault PDA
+    pub vault_id: u64,               // lets a user own several vaults
+    pub owner: Pubkey,
+    pub pending_owner: Option<Pubkey>,
+    pub vault_bump: u8,
+    pub state_bump: u8,
+    #[max_len(MAX_TOKEN_VAULTS)]
+    pub token_mints: Vec<Pubkey>,    // mints whose token vault is open, whatever its balance
+    pub unlock_at: i64,              // unix timestamp before which nothing can leave the vault (0 = unlocked)
+    pub withdraw_limit: u64,         // lamports withdrawable per window (0 = unlimited)
+    pub pending_withdraw_limit: u64, // raised limit waiting for its delay to pass
+    pub pending_limit_at: i64,       // when the pending limit takes effect (0 = nothing pending)
+    pub withdrawn_in_window: u64,
+    pub window_start: i64,
+    #[max_len(MAX_OWNERS)]
+    pub owners: Vec<Pubkey>,         // multisig owners (empty for a single-owner vault)
+    pub threshold: u8,               // approvals needed to execute a withdraw proposal (0 = single-owner vault)
+    pub proposal_count: u64,
+    pub guardian: Option<Pubkey>,    // can recover the lamports after an inactivity period
+    pub inactivity_period: i64,      // seconds without owner action before a recovery can complete
+    pub recovery_started_at: i64,    // 0 = no recovery in progress
+    pub recovery_address: Pubkey,
+    pub total_deposited: u64,        // lamports, lifetime
+    pub total_withdrawn: u64,        // lamports, lifetime
+    pub staked: u64,                 // lamports moved into the vault's stake account, 0 = none
+    pub delegates: u8,               // Delegate accounts not yet revoked
+}
+
+impl VaultState {
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.unlock_at
+    }
+
+    pub fn is_multisig(&self) -> bool {
+        self.threshold > 0
+    }
+
+    pub fn owner_index(&self, key: &Pubkey) -> Option<usize> {
+        self.owners.iter().position(|owner| owner == key)
+    }
+
+    // a balance can arrive without deposit_spl, so a vault stays tracked until close_spl removes it
+    pub fn track_token_vault(&mut self, mint: Pubkey) -> Result<()> {
+        if !self.token_mints.contains(&mint) {
+            require!(self.token_mints.len() < MAX_TOKEN_VAULTS, VaultError::TooManyTokenVaults);
+            self.token_mints.push(mint);
+        }
+        Ok(())
+    }
+
+    pub fn untrack_token_vault(&mut self, mint: &Pubkey) {
+        self.token_mints.retain(|tracked| tracked != mint);
+    }
+
+    pub fn set_owners(&mut self, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(owners.len() <= MAX_OWNERS, VaultError::TooManyOwners);
+        require!(threshold > 0 && threshold as usize <= owners.len(), VaultError::InvalidThreshold);
+        for (i, owner) in owners.iter().enumerate() {
+            require!(!owners[..i].contains(owner), VaultError::DuplicateOwner);
+        }
+
+        self.owners = owners;
+        self.threshold = threshold;
+        Ok(())
+    }
+
+    // any owner-signed instruction proves the key is not lost
+    pub fn record_owner_activity(&mut self) {
+        self.recovery_started_at = 0;
+    }
+
+    pub fn is_recovery_ready(&self, now: i64) -> bool {
+        self.recovery_started_at != 0
+            && now >= self.recovery_started_at.saturating_add(self.inactivity_period)
+    }
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_deposited = self.total_deposited.checked_add(amount).ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_withdrawn = self.total_withdrawn.checked_add(amount).ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn apply_pending_limit(&mut self, now: i64) {
+        if self.pending_limit_at != 0 && now >= self.pending_limit_at {
+            self.withdraw_limit = self.pending_withdraw_limit;
+            self.pending_withdraw_limit = 0;
+            self.pending_limit_at = 0;
+        }
+    }
+
+    // lowering the cap is immediate, raising (or removing) it only lands after LIMIT_INCREASE_DELAY
+    pub fn set_withdraw_limit(&mut self, limit: u64, now: i64) -> Result<()> {
+        self.apply_pending_limit(now);
+
+        let is_raise = self.withdraw_limit != 0 && (limit == 0 || limit > self.withdraw_limit);
+        if is_raise {
+            self.pending_withdraw_limit = limit;
+            self.pending_limit_at = now.checked_add(LIMIT_INCREASE_DELAY).ok_or(VaultError::Overflow)?;
+        } else {
+            self.withdraw_limit = limit;
+            self.pending_withdraw_limit = 0;
+            self.pending_limit_at = 0;
+        }
+        Ok(())
+    }
+
+    pub fn consume_allowance(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.apply_pending_limit(now);
+
+        if now >= self.window_start.saturating_add(WITHDRAW_WINDOW) {
+            self.window_start = now;
+            self.withdrawn_in_window = 0;
+        }
+
+        let withdrawn = self.withdrawn_in_window.checked_add(amount).ok_or(VaultError::Overflow)?;
+        require!(
+            self.withdraw_limit == 0 || withdrawn <= self.withdraw_limit,
+            VaultError::WithdrawLimitExceeded
+        );
+
+        self.withdrawn_in_window = withdrawn;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn vault_state(unlock_at: i64, withdraw_limit: u64) -> VaultState {
+        VaultState {
+            creator: Pubkey::default(),
+            vault_id: 0,
+            owner: Pubkey::default(),
+            pending_owner: None,
+            vault_bump: 0,
+            state_bump: 0,
+            token_mints: vec![],
+            unlock_at,
+            withdraw_limit,
+            pending_withdraw_limit: 0,
+            pending_limit_at: 0,
+            withdrawn_in_window: 0,
+            window_start: 0,
+            owners: vec![],
+            threshold: 0,
+            proposal_count: 0,
+            guardian: None,
+            inactivity_period: 0,
+            recovery_started_at: 0,
+            recovery_address: Pubkey::default(),
+            total_deposited: 0,
+            total_withdrawn: 0,
+            staked: 0,
+            delegates: 0,
+        }
+    }
+
+    #[test]
+    fn unset_lock_is_always_unlocked() {
+        assert!(!vault_state(0, 0).is_locked(0));
+        assert!(!vault_state(0, 0).is_locked(NOW));
+    }
+
+    #[test]
+    fn locked_until_unlock_at() {
+        let state = vault_state(NOW, 0);
+        assert!(state.is_locked(NOW - 1));
+        assert!(!state.is_locked(NOW));
+        assert!(!state.is_locked(NOW + 1));
+    }
+
+    #[test]
+    fn no_limit_allows_any_amount() {
+        let mut state = vault_state(0, 0);
+        assert!(state.consume_allowance(u64::MAX, NOW).is_ok());
+    }
+
+    #[test]
+    fn limit_is_enforced_within_window() {
+        let mut state = vault_state(0, 100);
+        assert!(state.consume_allowance(60, NOW).is_ok());
+        assert!(state.consume_allowance(40, NOW + 1).is_ok());
+        assert!(state.consume_allowance(1, NOW + WITHDRAW_WINDOW - 1).is_err());
+        assert_eq!(state.withdrawn_in_window, 100);
+    }
+
+    #[test]
+    fn window_resets_after_a_day() {
+        let mut state = vault_state(0, 100);
+        assert!(state.consume_allowance(100, NOW).is_ok());
+        assert!(state.consume_allowance(100, NOW + WITHDRAW_WINDOW).is_ok());
+        assert_eq!(state.window_start, NOW + WITHDRAW_WINDOW);
+    }
+
+    #[test]
+    fn lowering_the_limit_is_immediate() {
+        let mut state = vault_state(0, 100);
+        state.set_withdraw_limit(50, NOW).unwrap();
+        assert_eq!(state.withdraw_limit, 50);
+        assert!(state.consume_allowance(51, NOW).is_err());
+    }
+
+    #[test]
+    fn setting_a_first_limit_is_immediate() {
+        let mut state = vault_state(0, 0);
+        state.set_withdraw_limit(50, NOW).unwrap();
+        assert_eq!(state.withdraw_limit, 50);
+    }
+
+    #[test]
+    fn raising_the_limit_is_delayed() {
+        let mut state = vault_state(0, 100);
+        state.set_withdraw_limit(1_000, NOW).unwrap();
+        assert_eq!(state.withdraw_limit, 100);
+        assert!(state.consume_allowance(101, NOW + LIMIT_INCREASE_DELAY - 1).is_err());
+        assert!(state.consume_allowance(1_000, NOW + LIMIT_INCREASE_DELAY).is_ok());
+        assert_eq!(state.withdraw_limit, 1_000);
+        assert_eq!(state.pending_limit_at, 0);
+    }
+
+    #[test]
+    fn removing_the_limit_is_delayed() {
+        let mut state = vault_state(0, 100);
+        state.set_withdraw_limit(0, NOW).unwrap();
+        assert_eq!(state.withdraw_limit, 100);
+        state.apply_pending_limit(NOW + LIMIT_INCREASE_DELAY);
+        assert_eq!(state.withdraw_limit, 0);
+    }
+
+    #[test]
+    fn lowering_cancels_a_pending_raise() {
+        let mut state = vault_state(0, 100);
+        state.set_withdraw_limit(1_000, NOW).unwrap();
+        state.set_withdraw_limit(80, NOW + 1).unwrap();
+        state.apply_pending_limit(NOW + LIMIT_INCREASE_DELAY);
+        assert_eq!(state.withdraw_limit, 80);
+    }
+
+    #[test]
+    fn owners_are_validated() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state = vault_state(0, 0);
+        assert!(state.set_owners(vec![a, b], 0).is_err());
+        assert!(state.set_owners(vec![a, b], 3).is_err());
+        assert!(state.set_owners(vec![a, a], 1).is_err());
+        assert!(state.set_owners(vec![Pubkey::new_unique(); MAX_OWNERS + 1], 1).is_err());
+        assert!(!state.is_multisig());
+
+        state.set_owners(vec![a, b], 2).unwrap();
+        assert!(state.is_multisig());
+        assert_eq!(state.owner_index(&b), Some(1));
+        assert_eq!(state.owner_index(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn token_vaults_are_tracked_until_untracked() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state = vault_state(0, 0);
+        state.track_token_vault(a).unwrap();
+        state.track_token_vault(a).unwrap();
+        state.track_token_vault(b).unwrap();
+        assert_eq!(state.token_mints, vec![a, b]);
+
+        state.untrack_token_vault(&a);
+        assert_eq!(state.token_mints, vec![b]);
+
+        for _ in 1..MAX_TOKEN_VAULTS {
+            state.track_token_vault(Pubkey::new_unique()).unwrap();
+        }
+        assert!(state.track_token_vault(Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn recovery_waits_for_the_inactivity_period() {
+        let mut state = vault_state(0, 0);
+        state.inactivity_period = 100;
+        assert!(!state.is_recovery_ready(NOW));
+
+        state.recovery_started_at = NOW;
+        assert!(!state.is_recovery_ready(NOW + 99));
+        assert!(state.is_recovery_ready(NOW + 100));
+    }
+
+    #[test]
+    fn owner_activity_cancels_recovery() {
+        let mut state = vault_state(0, 0);
+        state.inactivity_period = 100;
+        state.recovery_started_at = NOW;
+        state.record_owner_activity();
+        assert!(!state.is_recovery_ready(NOW + 100));
+    }
+}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawProposal {
+    pub vault_state: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub approvals: u16, // bitmask over vault_state.owners
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl WithdrawProposal {
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
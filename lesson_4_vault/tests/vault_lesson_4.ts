@@ -1,12 +1,31 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Lesson4Vault } from "../target/types/lesson_4_vault";
+import { ASSOCIATED_TOKEN_PROGRAM_ID, createMint, getAccount, getOrCreateAssociatedTokenAccount, getAssociatedTokenAddressSync, mintTo, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { expect } from "chai";
 
 const publicKeyToUrl = (key: anchor.web3.PublicKey) => {
   return "https://explorer.solana.com/address/" + key.toBase58() + "?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899";
 }
 
+const airdrop = async (provider: anchor.AnchorProvider, to: anchor.web3.PublicKey, sol: number) => {
+  const signature = await provider.connection.requestAirdrop(to, sol * anchor.web3.LAMPORTS_PER_SOL);
+  const latestBlockhash = await provider.connection.getLatestBlockhash();
+  await provider.connection.confirmTransaction({
+    signature,
+    ...latestBlockhash,
+  }, "confirmed");
+}
+
+const expectError = async (promise: Promise<unknown>, code: string) => {
+  try {
+    await promise;
+    expect.fail(`expected ${code}`);
+  } catch (err) {
+    expect((err as anchor.AnchorError).error?.errorCode?.code).to.equal(code);
+  }
+}
+
 describe("lesson_4_vault", () => {
   // Configure the client to use the local cluster.
   const provider = anchor.AnchorProvider.env();
@@ -16,8 +35,10 @@ describe("lesson_4_vault", () => {
   const user = provider.wallet.publicKey;
 
   // Derive PDAs
+  const vaultId = new anchor.BN(0);
+
   const [vaultStatePda, stateBump] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("state"), user.toBuffer()],
+    [Buffer.from("state"), user.toBuffer(), vaultId.toArrayLike(Buffer, "le", 8)],
     program.programId
   );
 
@@ -30,6 +51,28 @@ describe("lesson_4_vault", () => {
 
   console.log(`See vaultPda: ${publicKeyToUrl(vaultPda)}`);
 
+  const eventsOf = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return [...parser.parseLogs(tx.meta.logMessages)];
+  };
+
+  const deriveVault = (owner: anchor.web3.PublicKey, id = 0) => {
+    const [state] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("state"), owner.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), state.toBuffer()],
+      program.programId
+    );
+    return [state, vault];
+  };
+
   before(async () => {
     // Airdrop for fees
     const signature = await provider.connection.requestAirdrop(user, 10 * anchor.web3.LAMPORTS_PER_SOL);
@@ -44,10 +87,11 @@ describe("lesson_4_vault", () => {
   });
 
   describe("after initializing the vault", async () => {
-    
+    let signature: string;
+
     before(async () => {
-      await program.methods
-        .initialize()
+      signature = await program.methods
+        .initialize(vaultId, new anchor.BN(0))
         .accountsStrict({
           user: user,
           vaultState: vaultStatePda,
@@ -59,6 +103,8 @@ describe("lesson_4_vault", () => {
 
     it("it creates the vault state account with correct bumps", async () => {
       const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+      expect(vaultState.vaultId.toNumber()).to.equal(vaultId.toNumber());
+      expect(vaultState.owner.toBase58()).to.equal(user.toBase58());
       expect(vaultState.stateBump).to.equal(stateBump);
       expect(vaultState.vaultBump).to.equal(vaultBump);
     });
@@ -68,6 +114,14 @@ describe("lesson_4_vault", () => {
       const rentExemption = await provider.connection.getMinimumBalanceForRentExemption(0);
       expect(vaultBalance).to.equal(rentExemption);
     });
+
+    it("it emits a VaultInitialized event", async () => {
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("vaultInitialized");
+      expect(event.data.vaultState.toBase58()).to.equal(vaultStatePda.toBase58());
+      expect(event.data.owner.toBase58()).to.equal(user.toBase58());
+      expect(event.data.vaultId.toNumber()).to.equal(vaultId.toNumber());
+    });
   });
 
   describe("after depositing SOL into the vault", async () => {
@@ -77,7 +131,7 @@ describe("lesson_4_vault", () => {
       const initialVaultBalance = await provider.connection.getBalance(vaultPda);
       const initialUserBalance = await provider.connection.getBalance(user);
 
-      await program.methods
+      const signature = await program.methods
         .deposit(new anchor.BN(depositAmount))
         .accountsStrict({
           user: user,
@@ -92,6 +146,14 @@ describe("lesson_4_vault", () => {
 
       expect(finalVaultBalance - initialVaultBalance).to.equal(depositAmount);
       expect(initialUserBalance - finalUserBalance - depositAmount).to.equal(5000);
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("deposited");
+      expect(event.data.amount.toNumber()).to.equal(depositAmount);
+      expect(event.data.totalDeposited.toNumber()).to.equal(depositAmount);
+
+      const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+      expect(vaultState.totalDeposited.toNumber()).to.equal(depositAmount);
     });
   });
 
@@ -102,7 +164,7 @@ describe("lesson_4_vault", () => {
       const initialVaultBalance = await provider.connection.getBalance(vaultPda);
       const initialUserBalance = await provider.connection.getBalance(user);
 
-      await program.methods
+      const signature = await program.methods
         .withdraw(new anchor.BN(withdrawAmount))
         .accountsStrict({
           user: user,
@@ -117,16 +179,135 @@ describe("lesson_4_vault", () => {
 
       expect(initialVaultBalance - finalVaultBalance).to.equal(withdrawAmount);
       expect(finalUserBalance - initialUserBalance - withdrawAmount).to.equal(-5000);
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("withdrawn");
+      expect(event.data.destination.toBase58()).to.equal(user.toBase58());
+      expect(event.data.amount.toNumber()).to.equal(withdrawAmount);
+      expect(event.data.totalWithdrawn.toNumber()).to.equal(withdrawAmount);
+
+      const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+      expect(vaultState.totalWithdrawn.toNumber()).to.equal(withdrawAmount);
     });
   });
 
+  for (const tokenProgram of [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]) {
+    describe(`after depositing SPL tokens into the vault (${tokenProgram.equals(TOKEN_PROGRAM_ID) ? "Token" : "Token-2022"})`, async () => {
+      const depositAmount = 1_000;
+      const withdrawAmount = 400;
+
+      let mint: anchor.web3.PublicKey;
+      let userAta: anchor.web3.PublicKey;
+      let tokenVault: anchor.web3.PublicKey;
+
+      const splAccounts = () => ({
+        user,
+        vaultState: vaultStatePda,
+        mint,
+        userAta,
+        tokenVault,
+        tokenProgram,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      });
+
+      before(async () => {
+        const payer = provider.wallet.payer;
+        mint = await createMint(provider.connection, payer, user, null, 6, undefined, undefined, tokenProgram);
+        userAta = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, user, false, undefined, undefined, tokenProgram)).address;
+        await mintTo(provider.connection, payer, mint, userAta, user, 2 * depositAmount, [], undefined, tokenProgram);
+        tokenVault = getAssociatedTokenAddressSync(mint, vaultStatePda, true, tokenProgram);
+
+        await program.methods
+          .depositSpl(new anchor.BN(depositAmount))
+          .accountsStrict(splAccounts())
+          .rpc();
+      });
+
+      it("it creates a token vault owned by the vault state", async () => {
+        const vaultAccount = await getAccount(provider.connection, tokenVault, undefined, tokenProgram);
+        expect(vaultAccount.owner.toBase58()).to.equal(vaultStatePda.toBase58());
+        expect(Number(vaultAccount.amount)).to.equal(depositAmount);
+      });
+
+      it("it tracks the token vault's mint", async () => {
+        const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+        expect(vaultState.tokenMints.map((tracked) => tracked.toBase58())).to.deep.equal([mint.toBase58()]);
+      });
+
+      it("it refuses to close the vault while the token vault holds a balance", async () => {
+        await expectError(
+          program.methods
+            .close()
+            .accountsStrict({
+              user: user,
+              vault: vaultPda,
+              vaultState: vaultStatePda,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc(),
+          "TokenVaultsNotEmpty"
+        );
+      });
+
+      it("the tokens are transfered back to the user on withdraw", async () => {
+        await program.methods
+          .withdrawSpl(new anchor.BN(withdrawAmount))
+          .accountsStrict(splAccounts())
+          .rpc();
+
+        const vaultAccount = await getAccount(provider.connection, tokenVault, undefined, tokenProgram);
+        const userAccount = await getAccount(provider.connection, userAta, undefined, tokenProgram);
+        expect(Number(vaultAccount.amount)).to.equal(depositAmount - withdrawAmount);
+        expect(Number(userAccount.amount)).to.equal(depositAmount + withdrawAmount);
+      });
+
+      it("a drained token vault still blocks closing until it is closed itself", async () => {
+        await program.methods
+          .withdrawSpl(new anchor.BN(depositAmount - withdrawAmount))
+          .accountsStrict(splAccounts())
+          .rpc();
+
+        // tokens can be sent straight to the vault ATA, so an empty balance proves nothing
+        const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+        expect(vaultState.tokenMints).to.have.length(1);
+        await expectError(
+          program.methods
+            .close()
+            .accountsStrict({
+              user: user,
+              vault: vaultPda,
+              vaultState: vaultStatePda,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc(),
+          "TokenVaultsNotEmpty"
+        );
+      });
+
+      it("closing the token vault returns the remaining tokens and rent", async () => {
+        await program.methods
+          .closeSpl()
+          .accountsStrict(splAccounts())
+          .rpc();
+
+        const userAccount = await getAccount(provider.connection, userAta, undefined, tokenProgram);
+        expect(Number(userAccount.amount)).to.equal(2 * depositAmount);
+        expect(await provider.connection.getAccountInfo(tokenVault)).to.be.null;
+
+        const vaultState = await program.account.vaultState.fetch(vaultStatePda);
+        expect(vaultState.tokenMints).to.be.empty;
+      });
+    });
+  }
+
   describe("after closing the vault", async () => {
     it("The rent-exempt amounts are transfered back to the user", async () => {
       const initialVaultBalance = await provider.connection.getBalance(vaultPda);
       const initialVaultStateBalance = await provider.connection.getBalance(vaultStatePda);
       const initialUserBalance = await provider.connection.getBalance(user);
 
-      await program.methods
+      const signature = await program.methods
         .close()
         .accountsStrict({
           user: user,
@@ -147,7 +328,972 @@ describe("lesson_4_vault", () => {
 
       // User gets back the remaining balance - fees
       expect(finalUserBalance).to.equal(initialUserBalance + initialVaultBalance + initialVaultStateBalance - 5000);
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("vaultClosed");
+      expect(event.data.owner.toBase58()).to.equal(user.toBase58());
+      expect(event.data.amount.toNumber()).to.equal(initialVaultBalance);
+    });
+  });
+
+  describe("when the vault is time-locked", async () => {
+    const locker = anchor.web3.Keypair.generate();
+    const lockDuration = 3; // seconds
+
+    const [lockedStatePda, lockedVaultPda] = deriveVault(locker.publicKey);
+
+    const lockedAccounts = {
+      user: locker.publicKey,
+      vaultState: lockedStatePda,
+      vault: lockedVaultPda,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    let unlockAt: number;
+
+    before(async () => {
+      await airdrop(provider, locker.publicKey, 2);
+
+      const slot = await provider.connection.getSlot();
+      unlockAt = (await provider.connection.getBlockTime(slot)) + lockDuration;
+
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(unlockAt))
+        .accountsStrict(lockedAccounts)
+        .signers([locker])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(lockedAccounts)
+        .signers([locker])
+        .rpc();
+    });
+
+    it("it stores the unlock timestamp", async () => {
+      const vaultState = await program.account.vaultState.fetch(lockedStatePda);
+      expect(vaultState.unlockAt.toNumber()).to.equal(unlockAt);
+    });
+
+    it("it refuses to withdraw before the unlock timestamp", async () => {
+      await expectError(
+        program.methods
+          .withdraw(new anchor.BN(1))
+          .accountsStrict(lockedAccounts)
+          .signers([locker])
+          .rpc(),
+        "VaultLocked"
+      );
+    });
+
+    it("it refuses to close before the unlock timestamp", async () => {
+      await expectError(
+        program.methods
+          .close()
+          .accountsStrict(lockedAccounts)
+          .signers([locker])
+          .rpc(),
+        "VaultLocked"
+      );
+    });
+
+    it("it refuses to shorten the lock", async () => {
+      await expectError(
+        program.methods
+          .extendLock(new anchor.BN(unlockAt - 1))
+          .accountsStrict({ user: locker.publicKey, vaultState: lockedStatePda })
+          .signers([locker])
+          .rpc(),
+        "LockNotExtended"
+      );
+    });
+
+    it("it allows to extend the lock", async () => {
+      unlockAt += 1;
+      await program.methods
+        .extendLock(new anchor.BN(unlockAt))
+        .accountsStrict({ user: locker.publicKey, vaultState: lockedStatePda })
+        .signers([locker])
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(lockedStatePda);
+      expect(vaultState.unlockAt.toNumber()).to.equal(unlockAt);
+    });
+
+    it("it allows to withdraw once the unlock timestamp is reached", async () => {
+      // wait until the cluster clock reaches unlock_at
+      for (;;) {
+        const slot = await provider.connection.getSlot();
+        if ((await provider.connection.getBlockTime(slot)) >= unlockAt) break;
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+
+      const initialVaultBalance = await provider.connection.getBalance(lockedVaultPda);
+      await program.methods
+        .withdraw(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(lockedAccounts)
+        .signers([locker])
+        .rpc();
+
+      const finalVaultBalance = await provider.connection.getBalance(lockedVaultPda);
+      expect(initialVaultBalance - finalVaultBalance).to.equal(anchor.web3.LAMPORTS_PER_SOL);
+    });
+  });
+
+  describe("when a withdrawal limit is set", async () => {
+    const saver = anchor.web3.Keypair.generate();
+    const limit = anchor.web3.LAMPORTS_PER_SOL / 2;
+
+    const [limitedStatePda, limitedVaultPda] = deriveVault(saver.publicKey);
+
+    const limitedAccounts = {
+      user: saver.publicKey,
+      vaultState: limitedStatePda,
+      vault: limitedVaultPda,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    before(async () => {
+      await airdrop(provider, saver.publicKey, 3);
+
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(0))
+        .accountsStrict(limitedAccounts)
+        .signers([saver])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(limitedAccounts)
+        .signers([saver])
+        .rpc();
+
+      await program.methods
+        .setWithdrawLimit(new anchor.BN(limit))
+        .accountsStrict({ user: saver.publicKey, vaultState: limitedStatePda })
+        .signers([saver])
+        .rpc();
+    });
+
+    it("a first limit takes effect immediately", async () => {
+      const vaultState = await program.account.vaultState.fetch(limitedStatePda);
+      expect(vaultState.withdrawLimit.toNumber()).to.equal(limit);
+    });
+
+    it("it allows to withdraw up to the limit", async () => {
+      await program.methods
+        .withdraw(new anchor.BN(limit))
+        .accountsStrict(limitedAccounts)
+        .signers([saver])
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(limitedStatePda);
+      expect(vaultState.withdrawnInWindow.toNumber()).to.equal(limit);
+    });
+
+    it("it refuses to withdraw above the remaining allowance", async () => {
+      await expectError(
+        program.methods
+          .withdraw(new anchor.BN(1))
+          .accountsStrict(limitedAccounts)
+          .signers([saver])
+          .rpc(),
+        "WithdrawLimitExceeded"
+      );
+    });
+
+    it("it refuses to close around the limit", async () => {
+      await expectError(
+        program.methods
+          .close()
+          .accountsStrict(limitedAccounts)
+          .signers([saver])
+          .rpc(),
+        "WithdrawLimitExceeded"
+      );
+    });
+
+    it("raising the limit is delayed", async () => {
+      await program.methods
+        .setWithdrawLimit(new anchor.BN(2 * limit))
+        .accountsStrict({ user: saver.publicKey, vaultState: limitedStatePda })
+        .signers([saver])
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(limitedStatePda);
+      expect(vaultState.withdrawLimit.toNumber()).to.equal(limit);
+      expect(vaultState.pendingWithdrawLimit.toNumber()).to.equal(2 * limit);
+      expect(vaultState.pendingLimitAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("lowering the limit takes effect immediately", async () => {
+      await program.methods
+        .setWithdrawLimit(new anchor.BN(limit / 2))
+        .accountsStrict({ user: saver.publicKey, vaultState: limitedStatePda })
+        .signers([saver])
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(limitedStatePda);
+      expect(vaultState.withdrawLimit.toNumber()).to.equal(limit / 2);
+      expect(vaultState.pendingLimitAt.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("when the vault is a multisig", async () => {
+    const [creator, cosigner, outsider] = [0, 1, 2].map(() => anchor.web3.Keypair.generate());
+    const thirdOwner = anchor.web3.Keypair.generate().publicKey;
+    const recipient = anchor.web3.Keypair.generate().publicKey;
+    const amount = anchor.web3.LAMPORTS_PER_SOL / 4;
+
+    const [multisigStatePda, multisigVaultPda] = deriveVault(creator.publicKey);
+
+    const multisigAccounts = {
+      user: creator.publicKey,
+      vaultState: multisigStatePda,
+      vault: multisigVaultPda,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    const deriveProposal = (id: number) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), multisigStatePda.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+    const propose = async (proposer: anchor.web3.Keypair, expiresIn: number) => {
+      const { proposalCount } = await program.account.vaultState.fetch(multisigStatePda);
+      const proposal = deriveProposal(proposalCount.toNumber());
+      const slot = await provider.connection.getSlot();
+      const expiresAt = (await provider.connection.getBlockTime(slot)) + expiresIn;
+
+      await program.methods
+        .proposeWithdraw(recipient, new anchor.BN(amount), new anchor.BN(expiresAt))
+        .accountsStrict({
+          proposer: proposer.publicKey,
+          vaultState: multisigStatePda,
+          proposal,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([proposer])
+        .rpc();
+      return proposal;
+    };
+
+    const approve = (owner: anchor.web3.Keypair, proposal: anchor.web3.PublicKey) => program.methods
+      .approveWithdraw()
+      .accountsStrict({ owner: owner.publicKey, vaultState: multisigStatePda, proposal })
+      .signers([owner])
+      .rpc();
+
+    const execute = (owner: anchor.web3.Keypair, proposal: anchor.web3.PublicKey, proposer: anchor.web3.PublicKey) => program.methods
+      .executeWithdraw()
+      .accountsStrict({
+        owner: owner.publicKey,
+        vaultState: multisigStatePda,
+        vault: multisigVaultPda,
+        proposal,
+        proposer,
+        recipient,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const cancel = (signer: anchor.web3.Keypair, proposal: anchor.web3.PublicKey, proposer: anchor.web3.PublicKey) => program.methods
+      .cancelWithdraw()
+      .accountsStrict({ signer: signer.publicKey, vaultState: multisigStatePda, proposal, proposer })
+      .signers([signer])
+      .rpc();
+
+    before(async () => {
+      await Promise.all([creator, cosigner, outsider].map((k) => airdrop(provider, k.publicKey, 2)));
+
+      await program.methods
+        .initializeMultisig(new anchor.BN(0), new anchor.BN(0), [creator.publicKey, cosigner.publicKey, thirdOwner], 2)
+        .accountsStrict(multisigAccounts)
+        .signers([creator])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(multisigAccounts)
+        .signers([creator])
+        .rpc();
+    });
+
+    it("it stores the owners and threshold", async () => {
+      const vaultState = await program.account.vaultState.fetch(multisigStatePda);
+      expect(vaultState.owners.map((o) => o.toBase58())).to.deep.equal(
+        [creator.publicKey, cosigner.publicKey, thirdOwner].map((o) => o.toBase58())
+      );
+      expect(vaultState.threshold).to.equal(2);
+    });
+
+    it("it refuses single-signer withdrawals", async () => {
+      await expectError(
+        program.methods
+          .withdraw(new anchor.BN(amount))
+          .accountsStrict(multisigAccounts)
+          .signers([creator])
+          .rpc(),
+        "MultisigVault"
+      );
+    });
+
+    it("it refuses SPL deposits, which only single-owner vaults can withdraw", async () => {
+      const payer = provider.wallet.payer;
+      const mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+      const userAta = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, creator.publicKey)).address;
+      await mintTo(provider.connection, payer, mint, userAta, payer, 100);
+
+      await expectError(
+        program.methods
+          .depositSpl(new anchor.BN(100))
+          .accountsStrict({
+            user: creator.publicKey,
+            vaultState: multisigStatePda,
+            mint,
+            userAta,
+            tokenVault: getAssociatedTokenAddressSync(mint, multisigStatePda, true),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc(),
+        "MultisigVault"
+      );
+    });
+
+    it("it refuses proposals from non-owners", async () => {
+      await expectError(propose(outsider, 60), "NotOwner");
+    });
+
+    describe("with a withdraw proposal", async () => {
+      let proposal: anchor.web3.PublicKey;
+
+      before(async () => {
+        proposal = await propose(creator, 60);
+      });
+
+      it("the proposer's approval is counted", async () => {
+        const proposalAccount = await program.account.withdrawProposal.fetch(proposal);
+        expect(proposalAccount.approvals).to.equal(1);
+        expect(proposalAccount.recipient.toBase58()).to.equal(recipient.toBase58());
+      });
+
+      it("it cannot be executed below the threshold", async () => {
+        await expectError(execute(creator, proposal, creator.publicKey), "ThresholdNotReached");
+      });
+
+      it("an owner cannot approve twice", async () => {
+        await expectError(approve(creator, proposal), "AlreadyApproved");
+      });
+
+      it("it cannot be cancelled by another owner before expiry", async () => {
+        await expectError(cancel(cosigner, proposal, creator.publicKey), "ProposalNotExpired");
+      });
+
+      it("it pays the recipient once the threshold is reached", async () => {
+        await approve(cosigner, proposal);
+        await execute(cosigner, proposal, creator.publicKey);
+
+        expect(await provider.connection.getBalance(recipient)).to.equal(amount);
+        expect(await provider.connection.getAccountInfo(proposal)).to.be.null;
+      });
+    });
+
+    it("the proposer can cancel a proposal", async () => {
+      const proposal = await propose(cosigner, 60);
+      await cancel(cosigner, proposal, cosigner.publicKey);
+      expect(await provider.connection.getAccountInfo(proposal)).to.be.null;
+    });
+
+    it("an expired proposal cannot be approved and can be cleaned up by any owner", async () => {
+      const proposal = await propose(creator, 2);
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      await expectError(approve(cosigner, proposal), "ProposalExpired");
+      await cancel(cosigner, proposal, creator.publicKey);
+      expect(await provider.connection.getAccountInfo(proposal)).to.be.null;
+    });
+  });
+
+  describe("when a delegate is approved", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const bot = anchor.web3.Keypair.generate();
+    const merchant = anchor.web3.Keypair.generate().publicKey;
+    const allowance = anchor.web3.LAMPORTS_PER_SOL / 2;
+
+    const [ownerStatePda, ownerVaultPda] = deriveVault(owner.publicKey);
+    const [delegatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), ownerStatePda.toBuffer(), bot.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const delegateWithdraw = (amount: number, destination = merchant) => program.methods
+      .delegateWithdraw(new anchor.BN(amount))
+      .accountsStrict({
+        delegate: bot.publicKey,
+        vaultState: ownerStatePda,
+        vault: ownerVaultPda,
+        delegateAccount: delegatePda,
+        destination,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bot])
+      .rpc();
+
+    before(async () => {
+      await Promise.all([owner, bot].map((k) => airdrop(provider, k.publicKey, 2)));
+
+      const ownerAccounts = {
+        user: owner.publicKey,
+        vaultState: ownerStatePda,
+        vault: ownerVaultPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(0))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .approveDelegate(bot.publicKey, new anchor.BN(allowance), new anchor.BN(0))
+        .accountsStrict({
+          user: owner.publicKey,
+          vaultState: ownerStatePda,
+          delegateAccount: delegatePda,
+          destination: merchant,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("it records the allowance and destination", async () => {
+      const delegate = await program.account.delegate.fetch(delegatePda);
+      expect(delegate.delegate.toBase58()).to.equal(bot.publicKey.toBase58());
+      expect(delegate.destination.toBase58()).to.equal(merchant.toBase58());
+      expect(delegate.allowance.toNumber()).to.equal(allowance);
+    });
+
+    it("the delegate can pull funds to the destination", async () => {
+      await delegateWithdraw(allowance / 2);
+
+      expect(await provider.connection.getBalance(merchant)).to.equal(allowance / 2);
+      const delegate = await program.account.delegate.fetch(delegatePda);
+      expect(delegate.allowance.toNumber()).to.equal(allowance / 2);
+    });
+
+    it("the delegate cannot pay out anywhere else", async () => {
+      await expectError(delegateWithdraw(1, bot.publicKey), "ConstraintHasOne");
+    });
+
+    it("the delegate cannot exceed its allowance", async () => {
+      await expectError(delegateWithdraw(allowance / 2 + 1), "AllowanceExceeded");
+    });
+
+    it("the vault cannot be closed while the delegate is outstanding", async () => {
+      // a vault re-created at the same address would otherwise revive the allowance
+      await expectError(
+        program.methods
+          .close()
+          .accountsStrict({
+            user: owner.publicKey,
+            vault: ownerVaultPda,
+            vaultState: ownerStatePda,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([owner])
+          .rpc(),
+        "DelegatesOutstanding"
+      );
+    });
+
+    it("the owner can revoke the delegate", async () => {
+      await program.methods
+        .revokeDelegate()
+        .accountsStrict({
+          user: owner.publicKey,
+          vaultState: ownerStatePda,
+          delegateAccount: delegatePda,
+        })
+        .signers([owner])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(delegatePda)).to.be.null;
+      await expectError(delegateWithdraw(1), "AccountNotInitialized");
+
+      const vaultState = await program.account.vaultState.fetch(ownerStatePda);
+      expect(vaultState.delegates).to.equal(0);
+    });
+  });
+
+  describe("when a guardian is set", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const guardian = anchor.web3.Keypair.generate();
+    const heir = anchor.web3.Keypair.generate().publicKey;
+    const inactivityPeriod = 2; // seconds
+
+    const [guardedStatePda, guardedVaultPda] = deriveVault(owner.publicKey);
+
+    const ownerAccounts = {
+      user: owner.publicKey,
+      vaultState: guardedStatePda,
+      vault: guardedVaultPda,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    const startRecovery = () => program.methods
+      .startRecovery(heir)
+      .accountsStrict({ guardian: guardian.publicKey, vaultState: guardedStatePda })
+      .signers([guardian])
+      .rpc();
+
+    const completeRecovery = () => program.methods
+      .completeRecovery()
+      .accountsStrict({
+        guardian: guardian.publicKey,
+        vaultState: guardedStatePda,
+        vault: guardedVaultPda,
+        recoveryAddress: heir,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([guardian])
+      .rpc();
+
+    before(async () => {
+      await Promise.all([owner, guardian].map((k) => airdrop(provider, k.publicKey, 2)));
+
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(0))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .setGuardian(guardian.publicKey, new anchor.BN(inactivityPeriod))
+        .accountsStrict({ user: owner.publicKey, vaultState: guardedStatePda })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("only the guardian can start a recovery", async () => {
+      await expectError(
+        program.methods
+          .startRecovery(heir)
+          .accountsStrict({ guardian: owner.publicKey, vaultState: guardedStatePda })
+          .signers([owner])
+          .rpc(),
+        "NotGuardian"
+      );
+    });
+
+    it("a recovery cannot complete before the inactivity period", async () => {
+      await startRecovery();
+      await expectError(completeRecovery(), "RecoveryNotReady");
+    });
+
+    it("any owner action cancels the recovery", async () => {
+      await program.methods
+        .deposit(new anchor.BN(1))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      const vaultState = await program.account.vaultState.fetch(guardedStatePda);
+      expect(vaultState.recoveryStartedAt.toNumber()).to.equal(0);
+
+      await new Promise((resolve) => setTimeout(resolve, (inactivityPeriod + 2) * 1000));
+      await expectError(completeRecovery(), "RecoveryNotReady");
+    });
+
+    it("the guardian moves the funds after the inactivity period", async () => {
+      await startRecovery();
+      await new Promise((resolve) => setTimeout(resolve, (inactivityPeriod + 2) * 1000));
+
+      const vaultBalance = await provider.connection.getBalance(guardedVaultPda);
+      await completeRecovery();
+
+      expect(await provider.connection.getBalance(heir)).to.equal(vaultBalance);
+      expect(await provider.connection.getBalance(guardedVaultPda)).to.equal(0);
+    });
+  });
+
+  describe("when a user owns several vaults", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const [spendingStatePda, spendingVaultPda] = deriveVault(owner.publicKey, 1);
+    const [savingsStatePda, savingsVaultPda] = deriveVault(owner.publicKey, 2);
+    const amount = anchor.web3.LAMPORTS_PER_SOL / 4;
+
+    const transferBetweenVaults = (
+      fromState: anchor.web3.PublicKey,
+      fromVault: anchor.web3.PublicKey,
+      toState: anchor.web3.PublicKey,
+      toVault: anchor.web3.PublicKey,
+    ) => program.methods
+      .transferBetweenVaults(new anchor.BN(amount))
+      .accountsStrict({
+        user: owner.publicKey,
+        fromState,
+        fromVault,
+        toState,
+        toVault,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    before(async () => {
+      await airdrop(provider, owner.publicKey, 2);
+
+      for (const [id, vaultState, vault] of [[1, spendingStatePda, spendingVaultPda], [2, savingsStatePda, savingsVaultPda]] as const) {
+        await program.methods
+          .initialize(new anchor.BN(id), new anchor.BN(0))
+          .accountsStrict({
+            user: owner.publicKey,
+            vaultState,
+            vault,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([owner])
+          .rpc();
+      }
+
+      await program.methods
+        .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          user: owner.publicKey,
+          vaultState: spendingStatePda,
+          vault: spendingVaultPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("each vault gets its own state and id", async () => {
+      const spending = await program.account.vaultState.fetch(spendingStatePda);
+      const savings = await program.account.vaultState.fetch(savingsStatePda);
+      expect(spending.vaultId.toNumber()).to.equal(1);
+      expect(savings.vaultId.toNumber()).to.equal(2);
+    });
+
+    it("lamports move between the user's vaults", async () => {
+      const initialSpending = await provider.connection.getBalance(spendingVaultPda);
+      const initialSavings = await provider.connection.getBalance(savingsVaultPda);
+
+      await transferBetweenVaults(spendingStatePda, spendingVaultPda, savingsStatePda, savingsVaultPda);
+
+      expect(initialSpending - await provider.connection.getBalance(spendingVaultPda)).to.equal(amount);
+      expect(await provider.connection.getBalance(savingsVaultPda) - initialSavings).to.equal(amount);
+    });
+
+    it("it refuses to transfer a vault into itself", async () => {
+      await expectError(
+        transferBetweenVaults(spendingStatePda, spendingVaultPda, spendingStatePda, spendingVaultPda),
+        "SameVault"
+      );
+    });
+  });
+
+  describe("when the vault ownership is transferred", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    const hardwareWallet = anchor.web3.Keypair.generate();
+
+    // the PDAs stay derived from the creator after the handover
+    const [movingStatePda, movingVaultPda] = deriveVault(owner.publicKey);
+
+    const withdraw = (signer: anchor.web3.Keypair) => program.methods
+      .withdraw(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 4))
+      .accountsStrict({
+        user: signer.publicKey,
+        vaultState: movingStatePda,
+        vault: movingVaultPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+
+    const acceptOwner = (signer: anchor.web3.Keypair) => program.methods
+      .acceptOwner()
+      .accountsStrict({ newOwner: signer.publicKey, vaultState: movingStatePda })
+      .signers([signer])
+      .rpc();
+
+    const proposeOwner = (newOwner: anchor.web3.PublicKey) => program.methods
+      .proposeOwner(newOwner)
+      .accountsStrict({ user: owner.publicKey, vaultState: movingStatePda })
+      .signers([owner])
+      .rpc();
+
+    before(async () => {
+      await Promise.all([owner, hardwareWallet].map((k) => airdrop(provider, k.publicKey, 2)));
+
+      const ownerAccounts = {
+        user: owner.publicKey,
+        vaultState: movingStatePda,
+        vault: movingVaultPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(0))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(ownerAccounts)
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .setGuardian(anchor.web3.Keypair.generate().publicKey, new anchor.BN(60))
+        .accountsStrict({ user: owner.publicKey, vaultState: movingStatePda })
+        .signers([owner])
+        .rpc();
+
+      await proposeOwner(hardwareWallet.publicKey);
+    });
+
+    it("the proposal does not change the owner yet", async () => {
+      const vaultState = await program.account.vaultState.fetch(movingStatePda);
+      expect(vaultState.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(vaultState.pendingOwner.toBase58()).to.equal(hardwareWallet.publicKey.toBase58());
+    });
+
+    it("only the proposed owner can accept", async () => {
+      await expectError(acceptOwner(owner), "NotPendingOwner");
+    });
+
+    it("the owner can withdraw the proposal", async () => {
+      await proposeOwner(anchor.web3.PublicKey.default);
+      const vaultState = await program.account.vaultState.fetch(movingStatePda);
+      expect(vaultState.pendingOwner).to.be.null;
+      await expectError(acceptOwner(hardwareWallet), "NotPendingOwner");
+
+      await proposeOwner(hardwareWallet.publicKey);
+    });
+
+    it("accepting waits until the old owner's delegates are revoked", async () => {
+      const bot = anchor.web3.Keypair.generate().publicKey;
+      const [delegatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("delegate"), movingStatePda.toBuffer(), bot.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .approveDelegate(bot, new anchor.BN(1_000), new anchor.BN(0))
+        .accountsStrict({
+          user: owner.publicKey,
+          vaultState: movingStatePda,
+          delegateAccount: delegatePda,
+          destination: bot,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      await expectError(acceptOwner(hardwareWallet), "DelegatesOutstanding");
+
+      await program.methods
+        .revokeDelegate()
+        .accountsStrict({ user: owner.publicKey, vaultState: movingStatePda, delegateAccount: delegatePda })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("the new owner controls the vault once accepted", async () => {
+      await acceptOwner(hardwareWallet);
+
+      const vaultState = await program.account.vaultState.fetch(movingStatePda);
+      expect(vaultState.owner.toBase58()).to.equal(hardwareWallet.publicKey.toBase58());
+      expect(vaultState.pendingOwner).to.be.null;
+      expect(vaultState.guardian).to.be.null;
+
+      await expectError(withdraw(owner), "NotOwner");
+      await withdraw(hardwareWallet);
+    });
+
+    it("the new owner can move lamports into their own vault with the same id", async () => {
+      const [ownStatePda, ownVaultPda] = deriveVault(hardwareWallet.publicKey);
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(0))
+        .accountsStrict({
+          user: hardwareWallet.publicKey,
+          vaultState: ownStatePda,
+          vault: ownVaultPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([hardwareWallet])
+        .rpc();
+
+      const amount = anchor.web3.LAMPORTS_PER_SOL / 4;
+      const initialBalance = await provider.connection.getBalance(ownVaultPda);
+      await program.methods
+        .transferBetweenVaults(new anchor.BN(amount))
+        .accountsStrict({
+          user: hardwareWallet.publicKey,
+          fromState: movingStatePda,
+          fromVault: movingVaultPda,
+          toState: ownStatePda,
+          toVault: ownVaultPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([hardwareWallet])
+        .rpc();
+
+      expect(await provider.connection.getBalance(ownVaultPda) - initialBalance).to.equal(amount);
+    });
+  });
+
+  describe("when vault lamports are staked", async () => {
+    const staker = anchor.web3.Keypair.generate();
+    const [stakingStatePda, stakingVaultPda] = deriveVault(staker.publicKey);
+    const [stakeAccountPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stake"), stakingStatePda.toBuffer()],
+      program.programId
+    );
+    const stakeAmount = anchor.web3.LAMPORTS_PER_SOL;
+    let gift: number; // lamports sent to the stake address before staking
+
+    const ownerAccounts = {
+      user: staker.publicKey,
+      vaultState: stakingStatePda,
+      vault: stakingVaultPda,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    const stakeAccounts = {
+      user: staker.publicKey,
+      vaultState: stakingStatePda,
+      vault: stakingVaultPda,
+      stakeAccount: stakeAccountPda,
+      stakeHistory: anchor.web3.SYSVAR_STAKE_HISTORY_PUBKEY,
+      stakeProgram: anchor.web3.StakeProgram.programId,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    };
+
+    const close = () => program.methods
+      .close()
+      .accountsStrict(ownerAccounts)
+      .signers([staker])
+      .rpc();
+
+    const delegate = async (amount: number) => {
+      // the local validator votes with its own vote account
+      const { current } = await provider.connection.getVoteAccounts();
+      const voteAccount = new anchor.web3.PublicKey(current[0].votePubkey);
+
+      return program.methods
+        .delegateStake(new anchor.BN(amount))
+        .accountsStrict({
+          ...stakeAccounts,
+          voteAccount,
+          stakeConfig: anchor.web3.STAKE_CONFIG_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker])
+        .rpc();
+    };
+
+    before(async () => {
+      await airdrop(provider, staker.publicKey, 3);
+
+      await program.methods
+        .initialize(new anchor.BN(0), new anchor.BN(0))
+        .accountsStrict(ownerAccounts)
+        .signers([staker])
+        .rpc();
+
+      await program.methods
+        .deposit(new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsStrict(ownerAccounts)
+        .signers([staker])
+        .rpc();
+
+      // anyone can pre-fund the stake address; that must not block staking
+      gift = await provider.connection.getMinimumBalanceForRentExemption(0);
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: stakeAccountPda,
+          lamports: gift,
+        })
+      ));
+
+      await delegate(stakeAmount);
+    });
+
+    it("the stake account is delegated with the vault as authority", async () => {
+      const stakeAccount = await provider.connection.getParsedAccountInfo(stakeAccountPda);
+      expect(stakeAccount.value.owner.toBase58()).to.equal(anchor.web3.StakeProgram.programId.toBase58());
+
+      const info = (stakeAccount.value.data as anchor.web3.ParsedAccountData).parsed.info;
+      expect(info.meta.authorized.staker).to.equal(stakingVaultPda.toBase58());
+      expect(info.meta.authorized.withdrawer).to.equal(stakingVaultPda.toBase58());
+      expect(Number(info.stake.delegation.stake)).to.equal(stakeAmount + gift);
+
+      const vaultState = await program.account.vaultState.fetch(stakingStatePda);
+      expect(vaultState.staked.toNumber()).to.equal(stakeAccount.value.lamports);
+    });
+
+    it("it cannot be closed while lamports are staked", async () => {
+      await expectError(close(), "StakeActive");
+    });
+
+    it("it cannot stake again while lamports are staked", async () => {
+      await expectError(delegate(stakeAmount / 2), "StakeActive");
+    });
+
+    it("the stake returns to the vault once deactivated", async () => {
+      await program.methods
+        .deactivateStake()
+        .accountsStrict({
+          user: staker.publicKey,
+          vaultState: stakingStatePda,
+          vault: stakingVaultPda,
+          stakeAccount: stakeAccountPda,
+          stakeProgram: anchor.web3.StakeProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .signers([staker])
+        .rpc();
+
+      // deactivated in the epoch it was delegated in, so it never warmed up and is withdrawable at once
+      const staked = await provider.connection.getBalance(stakeAccountPda);
+      const initialVaultBalance = await provider.connection.getBalance(stakingVaultPda);
+
+      await program.methods
+        .withdrawStake()
+        .accountsStrict(stakeAccounts)
+        .signers([staker])
+        .rpc();
+
+      expect(await provider.connection.getBalance(stakingVaultPda) - initialVaultBalance).to.equal(staked);
+      expect(await provider.connection.getBalance(stakeAccountPda)).to.equal(0);
+
+      const vaultState = await program.account.vaultState.fetch(stakingStatePda);
+      expect(vaultState.staked.toNumber()).to.equal(0);
+
+      await close();
     });
   });
 
-});
+});
\ No newline at end of file
@@ -0,0 +1,63 @@
+use anchor_lang::error_code;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Amount exceeds what the offer still asks for")]
+    FillExceedsOffer,
+    #[msg("Fill is too small to pay out any token A")]
+    FillTooSmall,
+    #[msg("Overflow")]
+    Overflow,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Offer expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("Offer is reserved for another taker")]
+    NotDesignatedTaker,
+    #[msg("An offer must keep some token A in the vault")]
+    EmptyOffer,
+    #[msg("Basket legs must be non-empty, distinct, non-zero and within the maximum")]
+    InvalidLegs,
+    #[msg("Remaining accounts do not match the basket legs")]
+    LegAccountMismatch,
+    #[msg("Asset offers need a Core asset on at least one side")]
+    NoAssetLeg,
+    #[msg("An account required by this leg is missing")]
+    MissingLegAccount,
+    #[msg("Invalid Asset")]
+    InvalidAsset,
+    #[msg("The fee must not exceed 1000 bps, nor the referral share 10000 bps")]
+    InvalidFee,
+    #[msg("Counter-offer expired")]
+    CounterExpired,
+    #[msg("Counter-offer has not expired and its offer is still open")]
+    CounterNotExpired,
+    #[msg("Offer holds less token A than when it was countered")]
+    OfferShrunk,
+    #[msg("Auction prices must decay from a start price to a non-zero floor over a non-empty window")]
+    InvalidAuction,
+    #[msg("Not supported on Dutch-auction offers")]
+    AuctionOffer,
+    #[msg("Offer is not a Dutch auction")]
+    NotAnAuction,
+    #[msg("Offer is priced worse than the taker expected")]
+    SlippageExceeded,
+    #[msg("Offer is priced worse than every offer in the full book")]
+    OfferBookFull,
+    #[msg("Listed escrows need their offer book")]
+    MissingOfferBook,
+    #[msg("Mints with a transfer fee or a transfer hook are not supported here")]
+    UnsupportedMint,
+    #[msg("Only the program's upgrade authority can set up the config")]
+    NotUpgradeAuthority,
+    #[msg("A taker cannot refer themselves")]
+    SelfReferral,
+    #[msg("Listed offers must hold at least one whole token A")]
+    DepositTooSmallToList,
+    #[msg("Listing pushes an offer out of the book, so that escrow must be passed in")]
+    MissingEvictedEscrow,
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct OfferUpdated {
+    pub escrow: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount}};
+
+use crate::{errors::EscrowError, token_extensions::{close_vault, transfer_checked_with_hook}, CounterOffer, Escrow, EscrowConfig, OfferBook};
+
+#[derive(Accounts)]
+pub struct AcceptCounter<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // gets the counter-offer rent back
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+
+    // both writable so withheld transfer fees can be harvested before the vaults close
+    #[account(mut, mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // gets back whatever the maker topped up after the counter was made
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = escrow.can_be_taken_by(taker.key) @ EscrowError::NotDesignatedTaker,
+        seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // required when the escrow is listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = escrow,
+        has_one = taker,
+        seeds = [b"counter", escrow.key().as_ref(), taker.key.as_ref()],
+        bump = counter_offer.bump,
+    )]
+    pub counter_offer: Box<Account<'info, CounterOffer>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter_offer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub counter_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"config".as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+
+    #[account(address = config.fee_recipient)]
+    pub fee_recipient: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program_b,
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AcceptCounter<'info> {
+    pub fn accept_counter(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(!self.counter_offer.is_expired(Clock::get()?.unix_timestamp), EscrowError::CounterExpired);
+        // the taker countered for what was on offer then, so a resized, part-filled or re-made offer cannot short them
+        require!(self.escrow.deposit >= self.counter_offer.deposit, EscrowError::OfferShrunk);
+
+        // a listed escrow takes its book entry with it
+        if self.escrow.listed {
+            let book = self.offer_book.as_mut().ok_or(EscrowError::MissingOfferBook)?;
+            book.remove(&self.escrow.key());
+        }
+
+        let le_bytes = self.escrow.seed.to_le_bytes();
+        let escrow_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            le_bytes.as_ref(),
+            &[self.escrow.bump],
+        ]];
+
+        // send the countered token A to the taker, and any top-up since back to the maker
+        let rest = self.vault.amount.checked_sub(self.counter_offer.deposit).ok_or(EscrowError::Overflow)?;
+        self.pay_a(self.taker_ata_a.to_account_info(), self.counter_offer.deposit, escrow_seeds, remaining_accounts)?;
+        self.pay_a(self.maker_ata_a.to_account_info(), rest, escrow_seeds, remaining_accounts)?;
+
+        // send token B from counter vault to treasury and maker; counters have no referrer
+        let escrow_key = self.escrow.key();
+        let counter_seeds: &[&[&[u8]]] = &[&[
+            b"counter",
+            escrow_key.as_ref(),
+            self.taker.key.as_ref(),
+            &[self.counter_offer.bump],
+        ]];
+
+        // the fee is on what the maker is owed: the whole proposal in net mode, otherwise what reached the vault.
+        // the maker gets the rest of the vault, which in net mode also covers the transfer fee on their share
+        let owed = if self.escrow.net_receive { self.counter_offer.proposed_receive } else { self.counter_vault.amount };
+        let split = self.config.split(owed, self.escrow.fee_bps, false)?;
+        let maker_share = self.counter_vault.amount.checked_sub(split.treasury).ok_or(EscrowError::Overflow)?;
+        self.pay_b(self.treasury_ata_b.to_account_info(), split.treasury, counter_seeds, remaining_accounts)?;
+        self.pay_b(self.maker_ata_b.to_account_info(), maker_share, counter_seeds, remaining_accounts)?;
+
+        // Close both vaults, each rent back to whoever funded it
+        let cpi_program = self.token_program_a.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, escrow_seeds);
+        close_vault(cpi_ctx, self.mint_a.to_account_info())?;
+
+        let cpi_program = self.token_program_b.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.counter_vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.counter_offer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, counter_seeds);
+        close_vault(cpi_ctx, self.mint_b.to_account_info())
+    }
+
+    // send token A from vault, skipping empty shares
+    fn pay_a(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_program = self.token_program_a.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to,
+            mint: self.mint_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, amount, self.mint_a.decimals)
+    }
+
+    // send token B from counter vault, skipping empty shares
+    fn pay_b(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_program = self.token_program_b.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.counter_vault.to_account_info(),
+            to,
+            mint: self.mint_b.to_account_info(),
+            authority: self.counter_offer.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, amount, self.mint_b.decimals)
+    }
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+// accounts of the leg that is not in use are passed as None
+pub fn required<T>(account: &Option<T>) -> Result<&T> {
+    account.as_ref().ok_or(error!(EscrowError::MissingLegAccount))
+}
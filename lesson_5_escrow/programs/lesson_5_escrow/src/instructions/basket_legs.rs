@@ -0,0 +1,105 @@
+use anchor_lang::{prelude::*, Owners};
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::{close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TransferChecked},
+};
+
+use crate::{errors::EscrowError, token_extensions::has_fee_or_hook, Leg};
+
+// remaining_accounts hold one [mint, from, to, token_program] group per leg
+pub const ACCOUNTS_PER_LEG: usize = 4;
+
+pub fn leg_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    legs: usize,
+) -> Result<std::slice::ChunksExact<'a, AccountInfo<'info>>> {
+    require!(remaining_accounts.len() == legs * ACCOUNTS_PER_LEG, EscrowError::LegAccountMismatch);
+    Ok(remaining_accounts.chunks_exact(ACCOUNTS_PER_LEG))
+}
+
+/// Mint and token program of a leg, checked against the basket.
+pub struct LegMint<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub decimals: u8,
+}
+
+impl<'a, 'info> LegMint<'a, 'info> {
+    pub fn load(leg: &Leg, mint: &'a AccountInfo<'info>, token_program: &'a AccountInfo<'info>) -> Result<Self> {
+        require_keys_eq!(*mint.key, leg.mint, EscrowError::LegAccountMismatch);
+        require!(
+            Mint::owners().contains(token_program.key) && mint.owner == token_program.key,
+            EscrowError::LegAccountMismatch
+        );
+        // legs move leg.amount with a fixed set of accounts, so a fee would short them and a hook would fail
+        require!(!has_fee_or_hook(mint)?, EscrowError::UnsupportedMint);
+
+        let decimals = Mint::try_deserialize(&mut &mint.data.borrow()[..])?.decimals;
+        Ok(Self { mint, token_program, decimals })
+    }
+
+    pub fn check_ata(&self, ata: &AccountInfo<'info>, authority: &Pubkey) -> Result<()> {
+        let expected = get_associated_token_address_with_program_id(authority, self.mint.key, self.token_program.key);
+        require_keys_eq!(*ata.key, expected, EscrowError::LegAccountMismatch);
+        Ok(())
+    }
+
+    pub fn amount(&self, ata: &AccountInfo<'info>) -> Result<u64> {
+        Ok(TokenAccount::try_deserialize(&mut &ata.data.borrow()[..])?.amount)
+    }
+
+    pub fn create_ata(
+        &self,
+        payer: &AccountInfo<'info>,
+        ata: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        associated_token_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        self.check_ata(ata, authority.key)?;
+
+        let cpi_accounts = Create {
+            payer: payer.clone(),
+            associated_token: ata.clone(),
+            authority: authority.clone(),
+            mint: self.mint.clone(),
+            system_program: system_program.clone(),
+            token_program: self.token_program.clone(),
+        };
+        create_idempotent(CpiContext::new(associated_token_program.clone(), cpi_accounts))
+    }
+
+    pub fn transfer(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: from.clone(),
+            to: to.clone(),
+            mint: self.mint.clone(),
+            authority: authority.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.clone(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, self.decimals)
+    }
+
+    pub fn close(
+        &self,
+        account: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_accounts = CloseAccount {
+            account: account.clone(),
+            destination: destination.clone(),
+            authority: authority.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.clone(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TransferChecked, TokenInterface, CloseAccount}};
+
+use crate::{errors::EscrowError, token_extensions::{close_vault, transfer_checked_with_hook}, Escrow, OfferBook};
+
+// Same as Refund, but anyone may crank it once the offer has expired
+#[derive(Accounts)]
+pub struct CleanupExpired<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut, mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    // the maker may have closed it since making the offer
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // required when the escrow is listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CleanupExpired<'info> {
+    pub fn cleanup_expired(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(self.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::OfferNotExpired);
+
+        // a listed escrow takes its book entry with it
+        if self.escrow.listed {
+            let book = self.offer_book.as_mut().ok_or(EscrowError::MissingOfferBook)?;
+            book.remove(&self.escrow.key());
+        }
+
+        let cpi_program = self.token_program_a.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let le_bytes = self.escrow.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            le_bytes.as_ref(),
+            &[self.escrow.bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        // vault rent goes back to the maker, not to whoever cranked the cleanup
+        let cpi_program = self.token_program_a.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        close_vault(cpi_ctx, self.mint_a.to_account_info())
+    }
+}
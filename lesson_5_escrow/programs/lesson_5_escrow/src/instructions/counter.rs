@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked}};
+
+use crate::{errors::EscrowError, token_extensions::{gross_of_fee, transfer_checked_with_hook}, CounterOffer, Escrow};
+
+#[derive(Accounts)]
+pub struct Counter<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        has_one = mint_b,
+        constraint = escrow.can_be_taken_by(taker.key) @ EscrowError::NotDesignatedTaker,
+        seeds = [b"escrow", escrow.maker.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = taker,
+        seeds = [b"counter", escrow.key().as_ref(), taker.key.as_ref()],
+        bump,
+        space = CounterOffer::DISCRIMINATOR.len() + CounterOffer::INIT_SPACE,
+    )]
+    pub counter_offer: Account<'info, CounterOffer>,
+
+    #[account(
+        init,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter_offer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub counter_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program_b: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Counter<'info> {
+    pub fn counter(
+        &mut self,
+        proposed_receive: u64,
+        expires_at: i64,
+        bumps: &CounterBumps,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(proposed_receive > 0, EscrowError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.escrow.is_expired(now), EscrowError::OfferExpired);
+        CounterOffer::validate_expiry(expires_at, now)?;
+
+        // in net mode the taker covers mint B's transfer fee twice, into the counter vault and out to the maker,
+        // so the maker gets the whole proposal as on a take
+        let mint_b = self.mint_b.to_account_info();
+        let deposit = if self.escrow.net_receive {
+            gross_of_fee(&mint_b, gross_of_fee(&mint_b, proposed_receive)?)?
+        } else {
+            proposed_receive
+        };
+
+        // lock token B from taker in the counter vault
+        let cpi_program = self.token_program_b.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            to: self.counter_vault.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, deposit, self.mint_b.decimals)?;
+
+        // net of any transfer fee, like the maker's deposit
+        self.counter_vault.reload()?;
+
+        self.counter_offer.set_inner(CounterOffer {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            mint_b: self.mint_b.key(),
+            deposit: self.escrow.deposit,
+            amount: self.counter_vault.amount,
+            proposed_receive,
+            expires_at,
+            bump: bumps.counter_offer,
+        });
+        Ok(())
+    }
+}
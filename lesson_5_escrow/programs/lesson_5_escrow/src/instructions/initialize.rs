@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked}};
 
-use crate::Escrow;
+use crate::{errors::EscrowError, token_extensions::transfer_checked_with_hook, BookEntry, DutchAuction, Escrow, EscrowConfig, OfferBook};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -41,6 +41,27 @@ pub struct Make<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    // optional: lists the offer in its pair's book
+    #[account(
+        init_if_needed,
+        payer = maker,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        space = OfferBook::DISCRIMINATOR.len() + OfferBook::INIT_SPACE,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
+    // required when listing pushes the book's most expensive offer out, so it can be unlisted
+    #[account(mut)]
+    pub evicted_escrow: Option<Box<Account<'info, Escrow>>>,
+
+    // the fee is read here and fixed on the escrow
+    #[account(
+        seeds = [b"config".as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+
     //pub token_program: Program<'info, Token> -> not generic, the legacy token program
     pub token_program_a: Interface<'info, TokenInterface>,
     pub token_program_b: Interface<'info, TokenInterface>,
@@ -50,7 +71,10 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn init_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_escrow(&mut self, seed: u64, deposit: u64, receive: u64, expires_at: i64, taker: Option<Pubkey>, net_receive: bool, bumps: &MakeBumps) -> Result<()> {
+        require!(expires_at == 0 || expires_at > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+
         // self.escrow.seeds = seeds;
         // self.escrow.receive = receive;
         // self.escrow.bump = bumps.escrow;
@@ -61,13 +85,30 @@ impl<'info> Make<'info> {
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
+            deposit,
             receive,
+            expires_at,
+            taker,
+            net_receive,
+            auction: None,
+            listed: false,
+            fee_bps: self.config.fee_bps,
             bump: bumps.escrow,
         });
         Ok(())
     }
 
-    pub fn deposit(&mut self, deposit: u64) -> Result<()> {
+    pub fn set_auction(&mut self, auction: DutchAuction) -> Result<()> {
+        auction.validate()?;
+        // the book is sorted by a fixed price
+        require!(self.offer_book.is_none(), EscrowError::AuctionOffer);
+
+        self.escrow.receive = auction.start_price;
+        self.escrow.auction = Some(auction);
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, deposit: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
         let cpi_program = self.token_program_a.to_account_info();
         let cpi_accounts = TransferChecked {
             from: self.maker_ata_a.to_account_info(),
@@ -75,7 +116,33 @@ impl<'info> Make<'info> {
             authority: self.maker.to_account_info(),
             mint: self.mint_a.to_account_info()
         };
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, deposit, self.mint_a.decimals)?;
+
+        // a transfer fee on mint A is withheld in the vault, so only what actually arrived is on offer
+        self.vault.reload()?;
+        self.escrow.deposit = self.vault.amount;
+        Ok(())
+    }
+
+    // after the deposit, so the entry has what actually reached the vault
+    pub fn list(&mut self, bumps: &MakeBumps) -> Result<()> {
+        let Some(book) = self.offer_book.as_mut() else {
+            return Ok(());
+        };
+
+        book.mint_a = self.mint_a.key();
+        book.mint_b = self.mint_b.key();
+        book.bump = bumps.offer_book.ok_or(EscrowError::MissingOfferBook)?;
+        OfferBook::check_listable(self.escrow.deposit, self.mint_a.decimals)?;
+
+        if let Some(evicted) = book.insert(BookEntry::for_escrow(self.escrow.key(), &self.escrow))? {
+            let evicted_escrow = self.evicted_escrow.as_mut().ok_or(EscrowError::MissingEvictedEscrow)?;
+            require_keys_eq!(evicted_escrow.key(), evicted, EscrowError::MissingEvictedEscrow);
+            evicted_escrow.listed = false;
+        }
+
+        self.escrow.listed = true;
+        Ok(())
     }
 }
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, program::Lesson5Escrow, EscrowConfig};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"config".as_ref()],
+        bump,
+        space = EscrowConfig::DISCRIMINATOR.len() + EscrowConfig::INIT_SPACE,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    // whoever can upgrade the program sets it up, so nobody can front-run the deployment
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::NotUpgradeAuthority)]
+    pub program: Program<'info, Lesson5Escrow>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::NotUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn initialize_config(
+        &mut self,
+        fee_bps: u16,
+        referral_bps: u16,
+        fee_recipient: Pubkey,
+        bumps: &InitializeConfigBumps,
+    ) -> Result<()> {
+        EscrowConfig::validate(fee_bps, referral_bps)?;
+
+        self.config.set_inner(EscrowConfig {
+            admin: self.admin.key(),
+            fee_bps,
+            referral_bps,
+            fee_recipient,
+            bump: bumps.config,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked}};
+use mpl_core::{instructions::TransferV1CpiBuilder, ID as CORE_PROGRAM_ID};
+
+use crate::{errors::EscrowError, token_extensions::transfer_checked_with_hook, AssetEscrow, EscrowLeg};
+use super::asset_legs::required;
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeAssetOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"asset_escrow", maker.key.as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = AssetEscrow::DISCRIMINATOR.len() + AssetEscrow::INIT_SPACE,
+    )]
+    pub escrow: Account<'info, AssetEscrow>,
+
+    // give leg: either a Core asset...
+    #[account(
+        mut,
+        constraint = give_asset.owner == &CORE_PROGRAM_ID @ EscrowError::InvalidAsset,
+    )]
+    /// CHECK: checked by core as well
+    pub give_asset: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    /// CHECK: checked by core
+    pub give_collection: Option<UncheckedAccount<'info>>,
+
+    // ...or a token
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub maker_ata_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a,
+    )]
+    pub vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program_a: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = CORE_PROGRAM_ID)]
+    /// CHECK: Verified by address constraint
+    pub core_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeAssetOffer<'info> {
+    pub fn make_asset_offer(
+        &mut self,
+        seed: u64,
+        give: EscrowLeg,
+        receive: EscrowLeg,
+        bumps: &MakeAssetOfferBumps,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        AssetEscrow::validate_legs(&give, &receive)?;
+
+        let give = match give {
+            EscrowLeg::Asset { asset } => {
+                // the escrow pda becomes the owner until take or refund
+                let give_asset = required(&self.give_asset)?;
+                require_keys_eq!(give_asset.key(), asset, EscrowError::LegAccountMismatch);
+
+                TransferV1CpiBuilder::new(&self.core_program.to_account_info())
+                    .asset(&give_asset.to_account_info())
+                    .collection(self.give_collection.as_ref().map(|c| c.as_ref()))
+                    .payer(&self.maker.to_account_info())
+                    .authority(Some(&self.maker.to_account_info()))
+                    .new_owner(&self.escrow.to_account_info())
+                    .system_program(Some(&self.system_program.to_account_info()))
+                    .invoke()?;
+                give
+            }
+            EscrowLeg::Token { mint, amount } => {
+                let mint_a = required(&self.mint_a)?;
+                require_keys_eq!(mint_a.key(), mint, EscrowError::LegAccountMismatch);
+
+                let cpi_program = required(&self.token_program_a)?.to_account_info();
+                let cpi_accounts = TransferChecked {
+                    from: required(&self.maker_ata_a)?.to_account_info(),
+                    to: required(&self.vault)?.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                    mint: mint_a.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(remaining_accounts.to_vec());
+                transfer_checked_with_hook(cpi_ctx, amount, mint_a.decimals)?;
+
+                // a transfer fee on mint A is withheld in the vault, so only what actually arrived is on offer
+                let vault = self.vault.as_mut().ok_or(EscrowError::MissingLegAccount)?;
+                vault.reload()?;
+                EscrowLeg::Token { mint, amount: vault.amount }
+            }
+        };
+
+        self.escrow.set_inner(AssetEscrow {
+            seed,
+            maker: self.maker.key(),
+            give,
+            receive,
+            bump: bumps.escrow,
+        });
+        Ok(())
+    }
+}
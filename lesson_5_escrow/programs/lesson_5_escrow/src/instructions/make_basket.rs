@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+
+use crate::{errors::EscrowError, BasketEscrow, Leg};
+use super::basket_legs::{leg_accounts, LegMint};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"basket", maker.key.as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = BasketEscrow::DISCRIMINATOR.len() + BasketEscrow::INIT_SPACE,
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBasket<'info> {
+    // remaining_accounts: [mint, maker_ata, vault, token_program] per give leg
+    pub fn make_basket(
+        &mut self,
+        seed: u64,
+        give: Vec<Leg>,
+        receive: Vec<Leg>,
+        bumps: &MakeBasketBumps,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        BasketEscrow::validate_legs(&give)?;
+        BasketEscrow::validate_legs(&receive)?;
+
+        let maker = self.maker.to_account_info();
+        let basket = self.basket.to_account_info();
+
+        for (leg, accounts) in give.iter().zip(leg_accounts(remaining_accounts, give.len())?) {
+            let [mint, maker_ata, vault, token_program] = accounts else {
+                return err!(EscrowError::LegAccountMismatch);
+            };
+            let leg_mint = LegMint::load(leg, mint, token_program)?;
+
+            leg_mint.create_ata(
+                &maker,
+                vault,
+                &basket,
+                &self.system_program.to_account_info(),
+                &self.associated_token_program.to_account_info(),
+            )?;
+            leg_mint.transfer(maker_ata, vault, &maker, leg.amount, &[])?;
+        }
+
+        self.basket.set_inner(BasketEscrow {
+            seed,
+            maker: self.maker.key(),
+            give,
+            receive,
+            bump: bumps.basket,
+        });
+        Ok(())
+    }
+}
@@ -1,7 +1,40 @@
 pub mod initialize;
 pub mod refund;
 pub mod take;
+pub mod take_partial;
+pub mod cleanup_expired;
+pub mod set_taker;
+pub mod update_offer;
+pub mod token_extensions;
+pub mod basket_legs;
+pub mod make_basket;
+pub mod take_basket;
+pub mod refund_basket;
+pub mod asset_legs;
+pub mod make_asset_offer;
+pub mod take_asset_offer;
+pub mod refund_asset_offer;
+pub mod initialize_config;
+pub mod update_config;
+pub mod counter;
+pub mod accept_counter;
+pub mod reclaim_counter;
 
 pub use initialize::*;
 pub use refund::*;
 pub use take::*;
+pub use take_partial::*;
+pub use cleanup_expired::*;
+pub use set_taker::*;
+pub use update_offer::*;
+pub use make_basket::*;
+pub use take_basket::*;
+pub use refund_basket::*;
+pub use make_asset_offer::*;
+pub use take_asset_offer::*;
+pub use refund_asset_offer::*;
+pub use initialize_config::*;
+pub use update_config::*;
+pub use counter::*;
+pub use accept_counter::*;
+pub use reclaim_counter::*;
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount};
+
+use crate::{errors::EscrowError, token_extensions::{close_vault, transfer_checked_with_hook}, CounterOffer, Escrow};
+
+// the escrow may be gone by now, so the counter-offer is found through its own fields
+#[derive(Accounts)]
+pub struct ReclaimCounter<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    // writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = taker,
+        has_one = mint_b,
+        seeds = [b"counter", counter_offer.escrow.as_ref(), taker.key.as_ref()],
+        bump = counter_offer.bump,
+    )]
+    pub counter_offer: Account<'info, CounterOffer>,
+
+    /// CHECK: only read, to tell whether the countered offer can still be accepted
+    #[account(address = counter_offer.escrow)]
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter_offer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub counter_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ReclaimCounter<'info> {
+    pub fn reclaim_counter(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.counter_offer.is_expired(Clock::get()?.unix_timestamp) || !self.can_still_be_accepted(),
+            EscrowError::CounterNotExpired
+        );
+
+        let cpi_program = self.token_program_b.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.counter_vault.to_account_info(),
+            to: self.taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            authority: self.counter_offer.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"counter",
+            self.counter_offer.escrow.as_ref(),
+            self.taker.key.as_ref(),
+            &[self.counter_offer.bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, self.counter_vault.amount, self.mint_b.decimals)?;
+
+        let cpi_program = self.token_program_b.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.counter_vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.counter_offer.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        close_vault(cpi_ctx, self.mint_b.to_account_info())
+    }
+
+    // accept_counter needs the escrow open, for this mint B and this taker
+    fn can_still_be_accepted(&self) -> bool {
+        let escrow = self.escrow.to_account_info();
+        if escrow.owner != &crate::ID {
+            return false;
+        }
+        let Ok(escrow) = Escrow::try_deserialize(&mut &escrow.data.borrow()[..]) else {
+            return false;
+        };
+        escrow.mint_b == self.counter_offer.mint_b && escrow.can_be_taken_by(self.taker.key)
+    }
+}
@@ -1,14 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token_interface::{Mint, TokenAccount, TransferChecked, transfer_checked, TokenInterface, close_account, CloseAccount}};
+use anchor_spl::{token_interface::{Mint, TokenAccount, TransferChecked, TokenInterface, CloseAccount}};
 
-use crate::Escrow;
+use crate::{errors::EscrowError, token_extensions::{close_vault, transfer_checked_with_hook}, Escrow, OfferBook};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
 
-    #[account(mint::token_program = token_program_a)]
+    // writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, mint::token_program = token_program_a)]
     pub mint_a: InterfaceAccount<'info, Mint>,
 
     #[account(
@@ -37,12 +38,26 @@ pub struct Refund<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    // required when the escrow is listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
     pub token_program_a: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Refund<'info> {
-    pub fn refund(&mut self) -> Result<()> {
+    pub fn refund(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        // a listed escrow takes its book entry with it
+        if self.escrow.listed {
+            let book = self.offer_book.as_mut().ok_or(EscrowError::MissingOfferBook)?;
+            book.remove(&self.escrow.key());
+        }
+
         let cpi_program = self.token_program_a.to_account_info();
         let cpi_accounts = TransferChecked {
             from: self.vault.to_account_info(),
@@ -59,8 +74,9 @@ impl<'info> Refund<'info> {
             &[self.escrow.bump],
         ]];
 
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
 
 
         let cpi_program = self.token_program_a.to_account_info();
@@ -71,7 +87,7 @@ impl<'info> Refund<'info> {
         };
 
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        close_account(cpi_ctx)
+        close_vault(cpi_ctx, self.mint_a.to_account_info())
 
         // to replace close = maker
         //let mut data = self.escrow.to_account_info().data.borrow_mut();
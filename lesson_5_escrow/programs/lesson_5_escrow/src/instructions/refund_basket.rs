@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+
+use crate::{errors::EscrowError, BasketEscrow};
+use super::basket_legs::{leg_accounts, LegMint};
+
+#[derive(Accounts)]
+pub struct RefundBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key.as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBasket<'info> {
+    // remaining_accounts: [mint, vault, maker_ata, token_program] per give leg
+    pub fn refund_basket(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let maker = self.maker.to_account_info();
+        let basket = self.basket.to_account_info();
+
+        let le_bytes = self.basket.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            self.maker.key.as_ref(),
+            le_bytes.as_ref(),
+            &[self.basket.bump],
+        ]];
+
+        for (leg, accounts) in self.basket.give.iter().zip(leg_accounts(remaining_accounts, self.basket.give.len())?) {
+            let [mint, vault, maker_ata, token_program] = accounts else {
+                return err!(EscrowError::LegAccountMismatch);
+            };
+            let leg_mint = LegMint::load(leg, mint, token_program)?;
+            leg_mint.check_ata(vault, &basket.key())?;
+
+            leg_mint.create_ata(
+                &maker,
+                maker_ata,
+                &maker,
+                &self.system_program.to_account_info(),
+                &self.associated_token_program.to_account_info(),
+            )?;
+            leg_mint.transfer(vault, maker_ata, &basket, leg_mint.amount(vault)?, signer_seeds)?;
+            leg_mint.close(vault, &maker, &basket, signer_seeds)?;
+        }
+        Ok(())
+    }
+}
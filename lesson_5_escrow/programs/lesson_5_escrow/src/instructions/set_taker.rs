@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::Escrow;
+
+#[derive(Accounts)]
+pub struct SetTaker<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> SetTaker<'info> {
+    pub fn set_taker(&mut self, taker: Option<Pubkey>) -> Result<()> {
+        // None opens the offer to anyone again
+        self.escrow.taker = taker;
+        Ok(())
+    }
+}
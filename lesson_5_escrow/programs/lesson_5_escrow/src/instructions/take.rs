@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, close_account, CloseAccount}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount}};
 
-use crate::Escrow;
+use crate::{errors::EscrowError, token_extensions::{close_vault, pay_with_fees, transfer_checked_with_hook}, Escrow, EscrowConfig, OfferBook};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -11,7 +11,8 @@ pub struct Take<'info> {
     #[account(mut)]
     pub taker: Signer<'info>,
 
-    #[account(mint::token_program = token_program_a)]
+    // writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, mint::token_program = token_program_a)]
     pub mint_a: InterfaceAccount<'info, Mint>,
 
     #[account(mint::token_program = token_program_b)]
@@ -22,7 +23,7 @@ pub struct Take<'info> {
         payer = taker,
         associated_token::mint = mint_b,
         associated_token::authority = maker,
-        associated_token::token_program = token_program_a,
+        associated_token::token_program = token_program_b,
     )]
     pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -49,6 +50,7 @@ pub struct Take<'info> {
         has_one = maker,
         has_one = mint_a,
         has_one = mint_b,
+        constraint = escrow.can_be_taken_by(taker.key) @ EscrowError::NotDesignatedTaker,
         seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
     )]
@@ -62,6 +64,44 @@ pub struct Take<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    // required when the escrow is listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
+    #[account(
+        seeds = [b"config".as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+
+    #[account(address = config.fee_recipient)]
+    pub fee_recipient: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program_b,
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(constraint = referrer.key() != taker.key() @ EscrowError::SelfReferral)]
+    pub referrer: Option<SystemAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = referrer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub referrer_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     //pub token_program: Program<'info, Token> -> not generic, the legacy token program
     pub token_program_a: Interface<'info, TokenInterface>,
     pub token_program_b: Interface<'info, TokenInterface>,
@@ -71,7 +111,40 @@ pub struct Take<'info> {
 }
 
 impl<'info> Take<'info> {
-    pub fn take(&mut self) -> Result<()> {
+    pub fn take(
+        &mut self,
+        expected_receive: u64,
+        min_deposit: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(self.escrow.auction.is_none(), EscrowError::AuctionOffer);
+
+        // the maker can amend the offer between the taker's read and this take
+        require!(
+            self.escrow.receive <= expected_receive && self.escrow.deposit >= min_deposit,
+            EscrowError::SlippageExceeded
+        );
+        self.fill(self.escrow.receive, remaining_accounts)
+    }
+
+    pub fn take_auction(&mut self, max_pay: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(self.escrow.auction.is_some(), EscrowError::NotAnAuction);
+
+        let price = self.escrow.price_at(Clock::get()?.unix_timestamp)?;
+        require!(price <= max_pay, EscrowError::SlippageExceeded);
+        self.fill(price, remaining_accounts)
+    }
+
+    // the whole vault for `amount_b` of token B
+    fn fill(&mut self, amount_b: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(!self.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::OfferExpired);
+
+        // a listed escrow takes its book entry with it
+        if self.escrow.listed {
+            let book = self.offer_book.as_mut().ok_or(EscrowError::MissingOfferBook)?;
+            book.remove(&self.escrow.key());
+        }
+
         // send token A from vault to taker
         let cpi_program = self.token_program_a.to_account_info();
         
@@ -90,20 +163,28 @@ impl<'info> Take<'info> {
             &[self.escrow.bump],
         ]];
 
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
 
-        // send token B from vault to maker
-        let cpi_program = self.token_program_b.to_account_info();
+        // send token B from taker to treasury, referrer and maker
         let cpi_accounts = TransferChecked {
             from: self.taker_ata_b.to_account_info(),
             to: self.maker_ata_b.to_account_info(),
             mint: self.mint_b.to_account_info(),
             authority: self.taker.to_account_info(),
         };
-
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)?;
+        let cpi_ctx = CpiContext::new(self.token_program_b.to_account_info(), cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        pay_with_fees(
+            cpi_ctx,
+            self.mint_b.decimals,
+            &self.config,
+            &self.escrow,
+            amount_b,
+            self.treasury_ata_b.to_account_info(),
+            self.referrer_ata_b.as_ref().map(|ata| ata.to_account_info()),
+        )?;
 
         // Close vault
         let cpi_program = self.token_program_a.to_account_info();
@@ -114,6 +195,6 @@ impl<'info> Take<'info> {
         };
 
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        close_account(cpi_ctx)
+        close_vault(cpi_ctx, self.mint_a.to_account_info())
     }
 }
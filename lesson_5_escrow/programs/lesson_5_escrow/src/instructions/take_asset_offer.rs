@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount}};
+use mpl_core::{instructions::TransferV1CpiBuilder, ID as CORE_PROGRAM_ID};
+
+use crate::{errors::EscrowError, token_extensions::{close_vault, transfer_checked_with_hook}, AssetEscrow, EscrowLeg};
+use super::asset_legs::required;
+
+#[derive(Accounts)]
+pub struct TakeAssetOffer<'info> {
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"asset_escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, AssetEscrow>,
+
+    // give leg, maker -> taker
+    #[account(mut)]
+    /// CHECK: checked against the escrow and by core
+    pub give_asset: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    /// CHECK: checked by core
+    pub give_collection: Option<UncheckedAccount<'info>>,
+
+    // writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, mint::token_program = token_program_a)]
+    pub mint_a: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a,
+    )]
+    pub vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub taker_ata_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program_a: Option<Interface<'info, TokenInterface>>,
+
+    // receive leg, taker -> maker
+    #[account(mut)]
+    /// CHECK: checked against the escrow and by core
+    pub receive_asset: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    /// CHECK: checked by core
+    pub receive_collection: Option<UncheckedAccount<'info>>,
+
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub taker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub maker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program_b: Option<Interface<'info, TokenInterface>>,
+
+    #[account(address = CORE_PROGRAM_ID)]
+    /// CHECK: Verified by address constraint
+    pub core_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeAssetOffer<'info> {
+    pub fn take_asset_offer(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        self.pay_maker(remaining_accounts)?;
+        self.release_to_taker(remaining_accounts)
+    }
+
+    fn pay_maker(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        match self.escrow.receive {
+            EscrowLeg::Asset { asset } => {
+                let receive_asset = required(&self.receive_asset)?;
+                require_keys_eq!(receive_asset.key(), asset, EscrowError::LegAccountMismatch);
+
+                // core rejects this unless the taker owns the asset
+                TransferV1CpiBuilder::new(&self.core_program.to_account_info())
+                    .asset(&receive_asset.to_account_info())
+                    .collection(self.receive_collection.as_ref().map(|c| c.as_ref()))
+                    .payer(&self.taker.to_account_info())
+                    .authority(Some(&self.taker.to_account_info()))
+                    .new_owner(&self.maker.to_account_info())
+                    .system_program(Some(&self.system_program.to_account_info()))
+                    .invoke()?;
+            }
+            EscrowLeg::Token { mint, amount } => {
+                let mint_b = required(&self.mint_b)?;
+                require_keys_eq!(mint_b.key(), mint, EscrowError::LegAccountMismatch);
+
+                let cpi_program = required(&self.token_program_b)?.to_account_info();
+                let cpi_accounts = TransferChecked {
+                    from: required(&self.taker_ata_b)?.to_account_info(),
+                    to: required(&self.maker_ata_b)?.to_account_info(),
+                    mint: mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(remaining_accounts.to_vec());
+                transfer_checked_with_hook(cpi_ctx, amount, mint_b.decimals)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn release_to_taker(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let le_bytes = self.escrow.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"asset_escrow",
+            self.maker.key.as_ref(),
+            le_bytes.as_ref(),
+            &[self.escrow.bump],
+        ]];
+
+        match self.escrow.give {
+            EscrowLeg::Asset { asset } => {
+                let give_asset = required(&self.give_asset)?;
+                require_keys_eq!(give_asset.key(), asset, EscrowError::LegAccountMismatch);
+
+                TransferV1CpiBuilder::new(&self.core_program.to_account_info())
+                    .asset(&give_asset.to_account_info())
+                    .collection(self.give_collection.as_ref().map(|c| c.as_ref()))
+                    .payer(&self.taker.to_account_info())
+                    .authority(Some(&self.escrow.to_account_info()))
+                    .new_owner(&self.taker.to_account_info())
+                    .system_program(Some(&self.system_program.to_account_info()))
+                    .invoke_signed(signer_seeds)?;
+            }
+            EscrowLeg::Token { mint, .. } => {
+                let mint_a = required(&self.mint_a)?;
+                require_keys_eq!(mint_a.key(), mint, EscrowError::LegAccountMismatch);
+                let vault = required(&self.vault)?;
+                let token_program_a = required(&self.token_program_a)?;
+
+                let cpi_accounts = TransferChecked {
+                    from: vault.to_account_info(),
+                    to: required(&self.taker_ata_a)?.to_account_info(),
+                    mint: mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(token_program_a.to_account_info(), cpi_accounts, signer_seeds)
+                    .with_remaining_accounts(remaining_accounts.to_vec());
+                transfer_checked_with_hook(cpi_ctx, vault.amount, mint_a.decimals)?;
+
+                let cpi_accounts = CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: self.maker.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(token_program_a.to_account_info(), cpi_accounts, signer_seeds);
+                close_vault(cpi_ctx, mint_a.to_account_info())?;
+            }
+        }
+        Ok(())
+    }
+}
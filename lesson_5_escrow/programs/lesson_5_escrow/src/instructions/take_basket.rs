@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+
+use crate::{errors::EscrowError, BasketEscrow};
+use super::basket_legs::{leg_accounts, LegMint, ACCOUNTS_PER_LEG};
+
+#[derive(Accounts)]
+pub struct TakeBasket<'info> {
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key.as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeBasket<'info> {
+    // remaining_accounts: [mint, taker_ata, maker_ata, token_program] per receive leg,
+    // then [mint, vault, taker_ata, token_program] per give leg
+    pub fn take_basket(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (receive_accounts, give_accounts) = remaining_accounts
+            .split_at_checked(self.basket.receive.len() * ACCOUNTS_PER_LEG)
+            .ok_or(EscrowError::LegAccountMismatch)?;
+
+        let maker = self.maker.to_account_info();
+        let taker = self.taker.to_account_info();
+        let basket = self.basket.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        let associated_token_program = self.associated_token_program.to_account_info();
+
+        // send every token B leg from taker to maker
+        for (leg, accounts) in self.basket.receive.iter().zip(leg_accounts(receive_accounts, self.basket.receive.len())?) {
+            let [mint, taker_ata, maker_ata, token_program] = accounts else {
+                return err!(EscrowError::LegAccountMismatch);
+            };
+            let leg_mint = LegMint::load(leg, mint, token_program)?;
+
+            leg_mint.create_ata(&taker, maker_ata, &maker, &system_program, &associated_token_program)?;
+            leg_mint.transfer(taker_ata, maker_ata, &taker, leg.amount, &[])?;
+        }
+
+        let le_bytes = self.basket.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            self.maker.key.as_ref(),
+            le_bytes.as_ref(),
+            &[self.basket.bump],
+        ]];
+
+        // send every token A vault to the taker and close it
+        for (leg, accounts) in self.basket.give.iter().zip(leg_accounts(give_accounts, self.basket.give.len())?) {
+            let [mint, vault, taker_ata, token_program] = accounts else {
+                return err!(EscrowError::LegAccountMismatch);
+            };
+            let leg_mint = LegMint::load(leg, mint, token_program)?;
+            leg_mint.check_ata(vault, &basket.key())?;
+
+            leg_mint.create_ata(&taker, taker_ata, &taker, &system_program, &associated_token_program)?;
+            leg_mint.transfer(vault, taker_ata, &basket, leg_mint.amount(vault)?, signer_seeds)?;
+            leg_mint.close(vault, &maker, &basket, signer_seeds)?;
+        }
+        Ok(())
+    }
+}
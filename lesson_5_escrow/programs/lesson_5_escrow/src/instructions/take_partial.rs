@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount}};
+
+use crate::{errors::EscrowError, token_extensions::{close_vault, pay_with_fees, transfer_checked_with_hook}, BookEntry, Escrow, EscrowConfig, OfferBook};
+
+#[derive(Accounts)]
+pub struct TakePartial<'info> {
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    // writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program_b,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    // no `close = maker`: the escrow stays open until the last fill
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = escrow.can_be_taken_by(taker.key) @ EscrowError::NotDesignatedTaker,
+        seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // required when the escrow is listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
+    #[account(
+        seeds = [b"config".as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+
+    #[account(address = config.fee_recipient)]
+    pub fee_recipient: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program_b,
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(constraint = referrer.key() != taker.key() @ EscrowError::SelfReferral)]
+    pub referrer: Option<SystemAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = referrer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub referrer_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakePartial<'info> {
+    pub fn take_partial(
+        &mut self,
+        amount_b: u64,
+        expected_receive: u64,
+        min_deposit: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::OfferExpired);
+        require!(self.escrow.priced_within(expected_receive, min_deposit), EscrowError::SlippageExceeded);
+
+        let payout = self.escrow.payout_for(amount_b)?;
+        self.escrow.record_fill(payout, amount_b)?;
+
+        // keep the book in step: the entry shrinks, or goes once the fill leaves less than a whole token A
+        if self.escrow.listed {
+            let book = self.offer_book.as_mut().ok_or(EscrowError::MissingOfferBook)?;
+            let key = self.escrow.key();
+            if self.escrow.is_filled() {
+                book.remove(&key);
+            } else {
+                self.escrow.listed = book.update(BookEntry::for_escrow(key, &self.escrow), self.mint_a.decimals)?;
+            }
+        }
+
+        // the last fill sweeps the vault, so the close below cannot fail on tokens sent to it directly
+        let amount_a = if self.escrow.is_filled() { self.vault.amount } else { payout };
+
+        // send token A from vault to taker
+        let cpi_program = self.token_program_a.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let le_bytes = self.escrow.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            le_bytes.as_ref(),
+            &[self.escrow.bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_ctx, amount_a, self.mint_a.decimals)?;
+
+        // send token B from taker to treasury, referrer and maker
+        let cpi_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program_b.to_account_info(), cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        pay_with_fees(
+            cpi_ctx,
+            self.mint_b.decimals,
+            &self.config,
+            &self.escrow,
+            amount_b,
+            self.treasury_ata_b.to_account_info(),
+            self.referrer_ata_b.as_ref().map(|ata| ata.to_account_info()),
+        )?;
+
+        if !self.escrow.is_filled() {
+            return Ok(());
+        }
+
+        // Close vault, then escrow
+        let cpi_program = self.token_program_a.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        close_vault(cpi_ctx, self.mint_a.to_account_info())?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
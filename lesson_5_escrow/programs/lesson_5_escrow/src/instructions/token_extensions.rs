@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+        },
+        onchain::invoke_transfer_checked,
+        state::Mint,
+    },
+    token_2022_extensions::{harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint},
+    token_interface::{close_account, CloseAccount, TransferChecked},
+};
+
+use crate::{errors::EscrowError, Escrow, EscrowConfig};
+
+// None for legacy mints and Token-2022 mints without a transfer fee
+fn fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Whether moving this mint takes a fee or calls a hook, which a fixed set of accounts cannot cover.
+/// The hook counts even without a program set, since its authority can set one later.
+pub fn has_fee_or_hook(mint: &AccountInfo) -> Result<bool> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().is_ok() || mint.get_extension::<TransferHook>().is_ok())
+}
+
+/// Amount to send so that at least `net` arrives after the mint's transfer fee.
+pub fn gross_of_fee(mint: &AccountInfo, net: u64) -> Result<u64> {
+    let Some(config) = fee_config(mint)? else {
+        return Ok(net);
+    };
+
+    let fee = config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, net)
+        .ok_or(EscrowError::Overflow)?;
+    Ok(net.checked_add(fee).ok_or(EscrowError::Overflow)?)
+}
+
+/// Pays a taker's `amount` of token B at the escrow's fee: the treasury and any referrer first, then the maker,
+/// whose account is `ctx.accounts.to`. In net mode the taker also covers the transfer fee on the maker's share.
+pub fn pay_with_fees<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    decimals: u8,
+    config: &EscrowConfig,
+    escrow: &Escrow,
+    amount: u64,
+    treasury: AccountInfo<'info>,
+    referrer: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let split = config.split(amount, escrow.fee_bps, referrer.is_some())?;
+    let maker_share = if escrow.net_receive {
+        gross_of_fee(&ctx.accounts.mint, split.maker)?
+    } else {
+        split.maker
+    };
+
+    let payees = [(Some(treasury), split.treasury), (referrer, split.referrer), (Some(ctx.accounts.to.clone()), maker_share)];
+    for (to, share) in payees {
+        // skip empty shares
+        let Some(to) = to.filter(|_| share > 0) else {
+            continue;
+        };
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.from.clone(),
+            to,
+            mint: ctx.accounts.mint.clone(),
+            authority: ctx.accounts.authority.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.program.clone(), cpi_accounts, ctx.signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.clone());
+        transfer_checked_with_hook(cpi_ctx, share, decimals)?;
+    }
+    Ok(())
+}
+
+/// `transfer_checked` that also passes along the extra accounts a transfer hook needs.
+/// They are looked up in `ctx.remaining_accounts`; mints without a hook ignore them.
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Closes a vault, first harvesting the transfer fees withheld in it to the mint,
+/// since Token-2022 refuses to close an account that still holds them.
+pub fn close_vault<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CloseAccount<'info>>,
+    mint: AccountInfo<'info>,
+) -> Result<()> {
+    if fee_config(&mint)?.is_some() {
+        let cpi_accounts = HarvestWithheldTokensToMint {
+            token_program_id: ctx.program.clone(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new(ctx.program.clone(), cpi_accounts);
+        harvest_withheld_tokens_to_mint(cpi_ctx, vec![ctx.accounts.account.clone()])?;
+    }
+
+    close_account(ctx)
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::EscrowConfig;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"config".as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn update_config(&mut self, fee_bps: u16, referral_bps: u16, fee_recipient: Pubkey) -> Result<()> {
+        EscrowConfig::validate(fee_bps, referral_bps)?;
+
+        self.config.fee_bps = fee_bps;
+        self.config.referral_bps = referral_bps;
+        self.config.fee_recipient = fee_recipient;
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{errors::EscrowError, events::OfferUpdated, token_extensions::transfer_checked_with_hook, BookEntry, Escrow, OfferBook};
+
+#[derive(Accounts)]
+pub struct UpdateOffer<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program_a,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // required when the escrow is listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Box<Account<'info, OfferBook>>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+}
+
+impl<'info> UpdateOffer<'info> {
+    pub fn update_offer(&mut self, new_receive: u64, top_up_or_withdraw_a: i64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        self.escrow.amend(new_receive, top_up_or_withdraw_a)?;
+
+        let cpi_program = self.token_program_a.to_account_info();
+        let amount = top_up_or_withdraw_a.unsigned_abs();
+
+        if top_up_or_withdraw_a > 0 {
+            // send more token A from maker to vault
+            let cpi_accounts = TransferChecked {
+                from: self.maker_ata_a.to_account_info(),
+                to: self.vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                authority: self.maker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(remaining_accounts.to_vec());
+            transfer_checked_with_hook(cpi_ctx, amount, self.mint_a.decimals)?;
+
+            // as in make, a transfer fee means less than `amount` arrived
+            self.vault.reload()?;
+            self.escrow.deposit = self.vault.amount;
+        } else if top_up_or_withdraw_a < 0 {
+            // send token A back from vault to maker
+            let cpi_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                to: self.maker_ata_a.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+
+            let le_bytes = self.escrow.seed.to_le_bytes();
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"escrow",
+                self.maker.key.as_ref(),
+                le_bytes.as_ref(),
+                &[self.escrow.bump],
+            ]];
+
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+                .with_remaining_accounts(remaining_accounts.to_vec());
+            transfer_checked_with_hook(cpi_ctx, amount, self.mint_a.decimals)?;
+        }
+
+        // the new price moves the entry, and a withdrawal down to dust takes it out
+        if self.escrow.listed {
+            let book = self.offer_book.as_mut().ok_or(EscrowError::MissingOfferBook)?;
+            self.escrow.listed = book.update(BookEntry::for_escrow(self.escrow.key(), &self.escrow), self.mint_a.decimals)?;
+        }
+
+        emit!(OfferUpdated {
+            escrow: self.escrow.key(),
+            deposit: self.escrow.deposit,
+            receive: self.escrow.receive,
+        });
+        Ok(())
+    }
+}
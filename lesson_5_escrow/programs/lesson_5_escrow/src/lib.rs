@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+pub mod errors;
+pub mod events;
 pub mod state;
 pub mod instructions;
 
@@ -13,17 +15,139 @@ pub mod lesson_5_escrow {
 
     use super::*;
 
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
-        ctx.accounts.init_escrow(seed, receive, &ctx.bumps)?;
-        ctx.accounts.deposit(deposit)
+    // remaining accounts carry the extra accounts of Token-2022 transfer hooks, if any
+    pub fn make<'info>(
+        ctx: Context<'_, '_, '_, 'info, Make<'info>>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+        net_receive: bool,
+    ) -> Result<()> {
+        ctx.accounts.init_escrow(seed, deposit, receive, expires_at, taker, net_receive, &ctx.bumps)?;
+        ctx.accounts.deposit(deposit, ctx.remaining_accounts)?;
+        ctx.accounts.list(&ctx.bumps)
     }
 
-    pub fn refund(ctx: Context<Refund>) -> Result<()> {
-        ctx.accounts.refund()
+    pub fn make_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, Make<'info>>,
+        seed: u64,
+        deposit: u64,
+        auction: DutchAuction,
+    ) -> Result<()> {
+        ctx.accounts.init_escrow(seed, deposit, auction.start_price, 0, None, false, &ctx.bumps)?;
+        ctx.accounts.set_auction(auction)?;
+        ctx.accounts.deposit(deposit, ctx.remaining_accounts)
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.take()
+    pub fn refund<'info>(ctx: Context<'_, '_, '_, 'info, Refund<'info>>) -> Result<()> {
+        ctx.accounts.refund(ctx.remaining_accounts)
+    }
+
+    pub fn take<'info>(
+        ctx: Context<'_, '_, '_, 'info, Take<'info>>,
+        expected_receive: u64,
+        min_deposit: u64,
+    ) -> Result<()> {
+        ctx.accounts.take(expected_receive, min_deposit, ctx.remaining_accounts)
+    }
+
+    pub fn take_auction<'info>(ctx: Context<'_, '_, '_, 'info, Take<'info>>, max_pay: u64) -> Result<()> {
+        ctx.accounts.take_auction(max_pay, ctx.remaining_accounts)
+    }
+
+    pub fn take_partial<'info>(
+        ctx: Context<'_, '_, '_, 'info, TakePartial<'info>>,
+        amount_b: u64,
+        expected_receive: u64,
+        min_deposit: u64,
+    ) -> Result<()> {
+        ctx.accounts.take_partial(amount_b, expected_receive, min_deposit, ctx.remaining_accounts)
+    }
+
+    pub fn cleanup_expired<'info>(ctx: Context<'_, '_, '_, 'info, CleanupExpired<'info>>) -> Result<()> {
+        ctx.accounts.cleanup_expired(ctx.remaining_accounts)
+    }
+
+    pub fn set_taker(ctx: Context<SetTaker>, taker: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.set_taker(taker)
+    }
+
+    pub fn update_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateOffer<'info>>,
+        new_receive: u64,
+        top_up_or_withdraw_a: i64,
+    ) -> Result<()> {
+        ctx.accounts.update_offer(new_receive, top_up_or_withdraw_a, ctx.remaining_accounts)
+    }
+
+    pub fn make_basket<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeBasket<'info>>,
+        seed: u64,
+        give: Vec<Leg>,
+        receive: Vec<Leg>,
+    ) -> Result<()> {
+        ctx.accounts.make_basket(seed, give, receive, &ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn take_basket<'info>(ctx: Context<'_, '_, '_, 'info, TakeBasket<'info>>) -> Result<()> {
+        ctx.accounts.take_basket(ctx.remaining_accounts)
+    }
+
+    pub fn refund_basket<'info>(ctx: Context<'_, '_, '_, 'info, RefundBasket<'info>>) -> Result<()> {
+        ctx.accounts.refund_basket(ctx.remaining_accounts)
+    }
+
+    pub fn make_asset_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeAssetOffer<'info>>,
+        seed: u64,
+        give: EscrowLeg,
+        receive: EscrowLeg,
+    ) -> Result<()> {
+        ctx.accounts.make_asset_offer(seed, give, receive, &ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn take_asset_offer<'info>(ctx: Context<'_, '_, '_, 'info, TakeAssetOffer<'info>>) -> Result<()> {
+        ctx.accounts.take_asset_offer(ctx.remaining_accounts)
+    }
+
+    pub fn refund_asset_offer<'info>(ctx: Context<'_, '_, '_, 'info, RefundAssetOffer<'info>>) -> Result<()> {
+        ctx.accounts.refund_asset_offer(ctx.remaining_accounts)
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        referral_bps: u16,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.initialize_config(fee_bps, referral_bps, fee_recipient, &ctx.bumps)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16,
+        referral_bps: u16,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.update_config(fee_bps, referral_bps, fee_recipient)
+    }
+
+    pub fn counter<'info>(
+        ctx: Context<'_, '_, '_, 'info, Counter<'info>>,
+        proposed_receive: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.counter(proposed_receive, expires_at, &ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn accept_counter<'info>(ctx: Context<'_, '_, '_, 'info, AcceptCounter<'info>>) -> Result<()> {
+        ctx.accounts.accept_counter(ctx.remaining_accounts)
+    }
+
+    pub fn reclaim_counter<'info>(ctx: Context<'_, '_, '_, 'info, ReclaimCounter<'info>>) -> Result<()> {
+        ctx.accounts.reclaim_counter(ctx.remaining_accounts)
     }
 }
 
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub enum EscrowLeg {
+    Token { mint: Pubkey, amount: u64 },
+    Asset { asset: Pubkey }, // Metaplex Core asset
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AssetEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub give: EscrowLeg,    // held by this account: the asset itself or a vault ATA
+    pub receive: EscrowLeg,
+    pub bump: u8,
+}
+
+impl AssetEscrow {
+    pub fn validate_legs(give: &EscrowLeg, receive: &EscrowLeg) -> Result<()> {
+        // token for token is what Escrow is for
+        require!(
+            matches!(give, EscrowLeg::Asset { .. }) || matches!(receive, EscrowLeg::Asset { .. }),
+            EscrowError::NoAssetLeg
+        );
+        for leg in [give, receive] {
+            if let EscrowLeg::Token { amount, .. } = leg {
+                require!(*amount > 0, EscrowError::InvalidAmount);
+            }
+        }
+        require!(give != receive, EscrowError::InvalidLegs);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset() -> EscrowLeg {
+        EscrowLeg::Asset { asset: Pubkey::new_unique() }
+    }
+
+    fn token(amount: u64) -> EscrowLeg {
+        EscrowLeg::Token { mint: Pubkey::new_unique(), amount }
+    }
+
+    #[test]
+    fn needs_at_least_one_asset() {
+        assert!(AssetEscrow::validate_legs(&asset(), &token(1)).is_ok());
+        assert!(AssetEscrow::validate_legs(&token(1), &asset()).is_ok());
+        assert!(AssetEscrow::validate_legs(&asset(), &asset()).is_ok());
+        assert!(AssetEscrow::validate_legs(&token(1), &token(1)).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_token_legs_and_self_swaps() {
+        assert!(AssetEscrow::validate_legs(&asset(), &token(0)).is_err());
+
+        let same = asset();
+        assert!(AssetEscrow::validate_legs(&same, &same).is_err());
+    }
+}
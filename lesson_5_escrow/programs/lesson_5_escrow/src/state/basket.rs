@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+pub const MAX_BASKET_LEGS: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub struct Leg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BasketEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_BASKET_LEGS)]
+    pub give: Vec<Leg>,    // one vault per leg, ATAs of this account
+    #[max_len(MAX_BASKET_LEGS)]
+    pub receive: Vec<Leg>,
+    pub bump: u8,
+}
+
+impl BasketEscrow {
+    pub fn validate_legs(legs: &[Leg]) -> Result<()> {
+        require!(!legs.is_empty() && legs.len() <= MAX_BASKET_LEGS, EscrowError::InvalidLegs);
+        for (i, leg) in legs.iter().enumerate() {
+            require!(leg.amount > 0, EscrowError::InvalidLegs);
+            // a duplicate would derive the same vault twice
+            require!(legs[..i].iter().all(|other| other.mint != leg.mint), EscrowError::InvalidLegs);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(amount: u64) -> Leg {
+        Leg { mint: Pubkey::new_unique(), amount }
+    }
+
+    #[test]
+    fn accepts_up_to_max_distinct_legs() {
+        let legs: Vec<Leg> = (0..MAX_BASKET_LEGS).map(|_| leg(1)).collect();
+        assert!(BasketEscrow::validate_legs(&legs).is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_or_oversized_baskets() {
+        assert!(BasketEscrow::validate_legs(&[]).is_err());
+
+        let legs: Vec<Leg> = (0..=MAX_BASKET_LEGS).map(|_| leg(1)).collect();
+        assert!(BasketEscrow::validate_legs(&legs).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_amounts_and_duplicate_mints() {
+        assert!(BasketEscrow::validate_legs(&[leg(1), leg(0)]).is_err());
+
+        let first = leg(1);
+        assert!(BasketEscrow::validate_legs(&[first, leg(2), first]).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct CounterOffer {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,    // token A on offer when the counter was made, all of it goes to the taker
+    pub amount: u64,     // token B locked in the counter vault
+    pub proposed_receive: u64, // token B the maker is to get, before the protocol fee
+    pub expires_at: i64, // the taker can reclaim from then on
+    pub bump: u8,
+}
+
+impl CounterOffer {
+    pub fn validate_expiry(expires_at: i64, now: i64) -> Result<()> {
+        require!(expires_at > now, EscrowError::InvalidExpiry);
+        Ok(())
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_always_expire() {
+        assert!(CounterOffer::validate_expiry(0, 1_000).is_err());
+        assert!(CounterOffer::validate_expiry(1_000, 1_000).is_err());
+        assert!(CounterOffer::validate_expiry(1_001, 1_000).is_ok());
+
+        let counter = CounterOffer {
+            escrow: Pubkey::default(),
+            taker: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            deposit: 10,
+            amount: 100,
+            proposed_receive: 100,
+            expires_at: 1_000,
+            bump: 0,
+        };
+        assert!(!counter.is_expired(999));
+        assert!(counter.is_expired(1_000));
+    }
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+/// Token B asked for decays linearly from `start_price` to `floor_price` between `start_ts` and `end_ts`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub struct DutchAuction {
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+impl DutchAuction {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.floor_price > 0 && self.start_price >= self.floor_price && self.end_ts > self.start_ts,
+            EscrowError::InvalidAuction
+        );
+        Ok(())
+    }
+
+    /// Price at `now`. The decay rounds down, so the price rounds up in favour of the maker.
+    pub fn price_at(&self, now: i64) -> Result<u64> {
+        if now <= self.start_ts {
+            return Ok(self.start_price);
+        }
+        if now >= self.end_ts {
+            return Ok(self.floor_price);
+        }
+
+        // widened, as the window may span more than i64::MAX seconds
+        let elapsed = (now as i128 - self.start_ts as i128) as u128;
+        let duration = (self.end_ts as i128 - self.start_ts as i128) as u128;
+        let decay = ((self.start_price - self.floor_price) as u128)
+            .checked_mul(elapsed)
+            .ok_or(EscrowError::Overflow)?
+            / duration;
+
+        // decay < start_price - floor_price since elapsed < duration
+        Ok(self.start_price - decay as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(start_price: u64, floor_price: u64) -> DutchAuction {
+        DutchAuction {
+            start_price,
+            floor_price,
+            start_ts: 1_000,
+            end_ts: 2_000,
+        }
+    }
+
+    #[test]
+    fn decays_linearly_to_the_floor() {
+        let auction = auction(1_000, 200);
+        assert_eq!(auction.price_at(0).unwrap(), 1_000);
+        assert_eq!(auction.price_at(1_000).unwrap(), 1_000);
+        assert_eq!(auction.price_at(1_500).unwrap(), 600);
+        assert_eq!(auction.price_at(2_000).unwrap(), 200);
+        assert_eq!(auction.price_at(i64::MAX).unwrap(), 200);
+    }
+
+    #[test]
+    fn rounds_up_in_favour_of_the_maker() {
+        // 1000 - 2 * 1 / 1000 = 999.998
+        assert_eq!(auction(1_000, 998).price_at(1_001).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn rejects_inverted_or_empty_auctions() {
+        assert!(auction(1_000, 200).validate().is_ok());
+        assert!(auction(1_000, 1_000).validate().is_ok());
+        assert!(auction(200, 1_000).validate().is_err());
+        assert!(auction(1_000, 0).validate().is_err());
+
+        let mut instant = auction(1_000, 200);
+        instant.end_ts = instant.start_ts;
+        assert!(instant.validate().is_err());
+    }
+
+    #[test]
+    fn does_not_overflow_on_large_prices() {
+        let auction = DutchAuction {
+            start_price: u64::MAX,
+            floor_price: 1,
+            start_ts: i64::MIN,
+            end_ts: i64::MAX,
+        };
+        assert!(auction.price_at(0).unwrap() > 1);
+    }
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, DutchAuction};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64, // token A still in the vault
+    pub receive: u64, // token B still asked for
+    pub expires_at: i64, // 0 = never
+    pub taker: Option<Pubkey>, // None = anyone
+    pub net_receive: bool, // maker gets `receive` after token B transfer fees
+    pub auction: Option<DutchAuction>, // None = fixed price `receive`
+    pub listed: bool, // has an entry in its pair's OfferBook
+    pub fee_bps: u16, // the config's fee when the offer was made, charged on every fill
+    pub bump: u8,
+}
+
+impl Escrow {
+    /// Token A paid out for `amount_b` of token B at the offer's current price.
+    /// Rounds down, so whatever dust is left over stays with the maker.
+    pub fn payout_for(&self, amount_b: u64) -> Result<u64> {
+        // an auction's price moves, so it is filled all at once
+        require!(self.auction.is_none(), EscrowError::AuctionOffer);
+        require!(amount_b > 0, EscrowError::InvalidAmount);
+        require!(amount_b <= self.receive, EscrowError::FillExceedsOffer);
+
+        if amount_b == self.receive {
+            return Ok(self.deposit);
+        }
+
+        let payout = (amount_b as u128)
+            .checked_mul(self.deposit as u128)
+            .ok_or(EscrowError::Overflow)?
+            / self.receive as u128;
+        require!(payout > 0, EscrowError::FillTooSmall);
+
+        // payout < deposit since amount_b < receive
+        Ok(payout as u64)
+    }
+
+    pub fn record_fill(&mut self, amount_a: u64, amount_b: u64) -> Result<()> {
+        self.deposit = self.deposit.checked_sub(amount_a).ok_or(EscrowError::Overflow)?;
+        self.receive = self.receive.checked_sub(amount_b).ok_or(EscrowError::Overflow)?;
+        Ok(())
+    }
+
+    /// Whether the offer is priced no worse for the taker than `expected_receive` for `min_deposit`.
+    /// Other fills shrink both sides of the offer, so only the ratio is compared.
+    pub fn priced_within(&self, expected_receive: u64, min_deposit: u64) -> bool {
+        self.receive as u128 * min_deposit as u128 <= expected_receive as u128 * self.deposit as u128
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.receive == 0
+    }
+
+    /// Reprices the offer and resizes it by `delta_a` token A (positive tops up, negative withdraws).
+    pub fn amend(&mut self, new_receive: u64, delta_a: i64) -> Result<()> {
+        require!(self.auction.is_none(), EscrowError::AuctionOffer);
+        require!(new_receive > 0, EscrowError::InvalidAmount);
+
+        self.deposit = if delta_a >= 0 {
+            self.deposit.checked_add(delta_a as u64).ok_or(EscrowError::Overflow)?
+        } else {
+            self.deposit.checked_sub(delta_a.unsigned_abs()).ok_or(EscrowError::Overflow)?
+        };
+        require!(self.deposit > 0, EscrowError::EmptyOffer);
+
+        self.receive = new_receive;
+        Ok(())
+    }
+
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        self.taker.is_none_or(|designated| designated == *taker)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    /// Token B asked for at `now`, for takes at the current price.
+    pub fn price_at(&self, now: i64) -> Result<u64> {
+        match &self.auction {
+            Some(auction) => auction.price_at(now),
+            None => Ok(self.receive),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(deposit: u64, receive: u64) -> Escrow {
+        Escrow {
+            seed: 0,
+            maker: Pubkey::default(),
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            deposit,
+            receive,
+            expires_at: 0,
+            taker: None,
+            net_receive: false,
+            auction: None,
+            listed: false,
+            fee_bps: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn pays_out_pro_rata() {
+        assert_eq!(escrow(100, 200).payout_for(50).unwrap(), 25);
+    }
+
+    #[test]
+    fn rounds_down_in_favour_of_the_maker() {
+        // 100 * 1 / 3 = 33.3
+        assert_eq!(escrow(100, 3).payout_for(1).unwrap(), 33);
+        assert!(escrow(1, 3).payout_for(1).is_err());
+    }
+
+    #[test]
+    fn the_last_fill_takes_the_remaining_deposit() {
+        let mut offer = escrow(100, 3);
+        for _ in 0..2 {
+            let payout = offer.payout_for(1).unwrap();
+            offer.record_fill(payout, 1).unwrap();
+        }
+        assert_eq!(offer.deposit, 34);
+        assert_eq!(offer.payout_for(1).unwrap(), 34);
+        offer.record_fill(34, 1).unwrap();
+        assert!(offer.is_filled());
+        assert_eq!(offer.deposit, 0);
+    }
+
+    #[test]
+    fn partial_fills_keep_the_quoted_price() {
+        let mut offer = escrow(100, 200);
+        assert!(offer.priced_within(200, 100));
+
+        // another taker's fill leaves the price where it was
+        offer.record_fill(25, 50).unwrap();
+        assert!(offer.priced_within(200, 100));
+
+        // the maker asks for more token B for the same token A
+        offer.amend(300, 0).unwrap();
+        assert!(!offer.priced_within(200, 100));
+    }
+
+    #[test]
+    fn rejects_overfills() {
+        assert!(escrow(100, 200).payout_for(201).is_err());
+        assert!(escrow(100, 200).payout_for(0).is_err());
+    }
+
+    #[test]
+    fn expires_only_when_an_expiry_is_set() {
+        let mut offer = escrow(100, 200);
+        assert!(!offer.is_expired(i64::MAX));
+
+        offer.expires_at = 1_000;
+        assert!(!offer.is_expired(999));
+        assert!(offer.is_expired(1_000));
+    }
+
+    #[test]
+    fn only_the_designated_taker_can_take() {
+        let mut offer = escrow(100, 200);
+        let desk = Pubkey::new_unique();
+        assert!(offer.can_be_taken_by(&desk));
+
+        offer.taker = Some(desk);
+        assert!(offer.can_be_taken_by(&desk));
+        assert!(!offer.can_be_taken_by(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn amends_price_and_size() {
+        let mut offer = escrow(100, 200);
+        offer.amend(300, 50).unwrap();
+        assert_eq!((offer.deposit, offer.receive), (150, 300));
+
+        offer.amend(100, -120).unwrap();
+        assert_eq!((offer.deposit, offer.receive), (30, 100));
+    }
+
+    #[test]
+    fn amending_cannot_empty_the_offer() {
+        assert!(escrow(100, 200).amend(200, -100).is_err());
+        assert!(escrow(100, 200).amend(200, -101).is_err());
+        assert!(escrow(100, 200).amend(0, 0).is_err());
+    }
+
+    #[test]
+    fn auctions_are_priced_by_the_clock_and_cannot_be_split_or_amended() {
+        let mut offer = escrow(100, 1_000);
+        assert_eq!(offer.price_at(1_500).unwrap(), 1_000);
+
+        offer.auction = Some(DutchAuction { start_price: 1_000, floor_price: 200, start_ts: 1_000, end_ts: 2_000 });
+        assert_eq!(offer.price_at(1_500).unwrap(), 600);
+        assert!(offer.payout_for(500).is_err());
+        assert!(offer.amend(500, 0).is_err());
+    }
+
+    #[test]
+    fn does_not_overflow_on_large_amounts() {
+        assert_eq!(escrow(u64::MAX, u64::MAX).payout_for(u64::MAX - 1).unwrap(), u64::MAX - 1);
+    }
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+pub const MAX_BPS: u16 = 10_000;
+// 10%, so a compromised or careless admin cannot take most of a trade
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowConfig {
+    pub admin: Pubkey,
+    pub fee_bps: u16,      // of every token B payment, fixed on each escrow at make
+    pub referral_bps: u16, // of the fee, paid to the referrer when there is one
+    pub fee_recipient: Pubkey,
+    pub bump: u8,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub maker: u64,
+    pub treasury: u64,
+    pub referrer: u64,
+}
+
+impl EscrowConfig {
+    pub fn validate(fee_bps: u16, referral_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS && referral_bps <= MAX_BPS, EscrowError::InvalidFee);
+        Ok(())
+    }
+
+    /// Splits a token B payment at the escrow's `fee_bps`, not the current one, so the admin
+    /// cannot raise the fee on open offers. Fees round down, so the maker never gets less than their share.
+    pub fn split(&self, amount: u64, fee_bps: u16, has_referrer: bool) -> Result<FeeSplit> {
+        let fee = bps_of(amount, fee_bps)?;
+        let referrer = if has_referrer { bps_of(fee, self.referral_bps)? } else { 0 };
+
+        Ok(FeeSplit {
+            maker: amount.checked_sub(fee).ok_or(EscrowError::Overflow)?,
+            treasury: fee.checked_sub(referrer).ok_or(EscrowError::Overflow)?,
+            referrer,
+        })
+    }
+}
+
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let share = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(EscrowError::Overflow)?
+        / MAX_BPS as u128;
+    u64::try_from(share).map_err(|_| error!(EscrowError::Overflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(referral_bps: u16) -> EscrowConfig {
+        EscrowConfig {
+            admin: Pubkey::default(),
+            fee_bps: 0,
+            referral_bps,
+            fee_recipient: Pubkey::default(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn no_fee_pays_the_maker_in_full() {
+        let split = config(5_000).split(1_000, 0, true).unwrap();
+        assert_eq!(split, FeeSplit { maker: 1_000, treasury: 0, referrer: 0 });
+    }
+
+    #[test]
+    fn splits_the_fee_with_the_referrer() {
+        // 1% fee, a quarter of it to the referrer
+        let split = config(2_500).split(10_000, 100, true).unwrap();
+        assert_eq!(split, FeeSplit { maker: 9_900, treasury: 75, referrer: 25 });
+    }
+
+    #[test]
+    fn the_treasury_keeps_the_whole_fee_without_a_referrer() {
+        let split = config(2_500).split(10_000, 100, false).unwrap();
+        assert_eq!(split, FeeSplit { maker: 9_900, treasury: 100, referrer: 0 });
+    }
+
+    #[test]
+    fn rounds_fees_down() {
+        // 0.3% of 999 = 2.997
+        let split = config(5_000).split(999, 30, true).unwrap();
+        assert_eq!(split, FeeSplit { maker: 997, treasury: 1, referrer: 1 });
+    }
+
+    #[test]
+    fn parts_always_add_up() {
+        let config = config(3_333);
+        for amount in [0, 1, 73, 10_001, u64::MAX] {
+            let split = config.split(amount, 137, true).unwrap();
+            assert_eq!(split.maker as u128 + split.treasury as u128 + split.referrer as u128, amount as u128);
+        }
+    }
+
+    #[test]
+    fn rejects_shares_above_100_percent() {
+        assert!(EscrowConfig::validate(MAX_FEE_BPS, MAX_BPS).is_ok());
+        assert!(EscrowConfig::validate(0, MAX_BPS + 1).is_err());
+    }
+
+    #[test]
+    fn caps_the_fee() {
+        assert!(EscrowConfig::validate(MAX_FEE_BPS + 1, 0).is_err());
+        assert!(EscrowConfig::validate(MAX_BPS, 0).is_err());
+    }
+}
@@ -1,12 +1,20 @@
-use anchor_lang::prelude::*;
-
-#[account]
-#[derive(InitSpace)]
-pub struct Escrow {
-    pub seed: u64,
-    pub maker: Pubkey,
-    pub mint_a: Pubkey,
-    pub mint_b: Pubkey,
-    pub receive: u64,
-    pub bump: u8,
-}
+pub mod escrow;
+pub use escrow::*;
+
+pub mod basket;
+pub use basket::*;
+
+pub mod asset_escrow;
+pub use asset_escrow::*;
+
+pub mod escrow_config;
+pub use escrow_config::*;
+
+pub mod counter_offer;
+pub use counter_offer::*;
+
+pub mod dutch_auction;
+pub use dutch_auction::*;
+
+pub mod offer_book;
+pub use offer_book::*;
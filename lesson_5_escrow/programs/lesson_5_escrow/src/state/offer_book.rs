@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, Escrow};
+
+pub const MAX_BOOK_ENTRIES: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub struct BookEntry {
+    pub escrow: Pubkey,
+    pub deposit: u64, // token A on offer
+    pub receive: u64, // token B asked for
+}
+
+impl BookEntry {
+    pub fn for_escrow(key: Pubkey, escrow: &Escrow) -> Self {
+        Self {
+            escrow: key,
+            deposit: escrow.deposit,
+            receive: escrow.receive,
+        }
+    }
+
+    // token B per token A, compared without dividing
+    fn cheaper_than(&self, other: &BookEntry) -> bool {
+        (self.receive as u128) * (other.deposit as u128) < (other.receive as u128) * (self.deposit as u128)
+    }
+}
+
+/// Live offers for one pair, cheapest first, so clients get the best prices in one fetch.
+#[account]
+#[derive(InitSpace)]
+pub struct OfferBook {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    #[max_len(MAX_BOOK_ENTRIES)]
+    pub entries: Vec<BookEntry>,
+    pub bump: u8,
+}
+
+impl OfferBook {
+    /// At least one whole token A, so a full book cannot be flooded with dust offers
+    /// that push out the real ones.
+    pub fn check_listable(deposit: u64, decimals: u8) -> Result<()> {
+        let one_token = 10u64.checked_pow(decimals.into()).ok_or(EscrowError::Overflow)?;
+        require!(deposit >= one_token, EscrowError::DepositTooSmallToList);
+        Ok(())
+    }
+
+    /// Inserts behind every offer at the same or a better price. When the book is full
+    /// the most expensive offer drops out, unless the new one would be that offer,
+    /// and its escrow is returned so the caller can unlist it.
+    pub fn insert(&mut self, entry: BookEntry) -> Result<Option<Pubkey>> {
+        let at = self.entries.partition_point(|listed| !entry.cheaper_than(listed));
+        require!(at < MAX_BOOK_ENTRIES, EscrowError::OfferBookFull);
+
+        self.entries.insert(at, entry);
+        Ok(if self.entries.len() > MAX_BOOK_ENTRIES {
+            self.entries.pop().map(|evicted| evicted.escrow)
+        } else {
+            None
+        })
+    }
+
+    pub fn remove(&mut self, escrow: &Pubkey) {
+        self.entries.retain(|listed| listed.escrow != *escrow);
+    }
+
+    /// Re-sorts an offer after a fill or an amend. The offer makes room for itself, so nothing drops out.
+    /// An offer shrunk below one whole token A leaves the book instead; returns whether it is still listed.
+    pub fn update(&mut self, entry: BookEntry, decimals: u8) -> Result<bool> {
+        self.remove(&entry.escrow);
+        if Self::check_listable(entry.deposit, decimals).is_err() {
+            return Ok(false);
+        }
+
+        self.insert(entry)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(deposit: u64, receive: u64) -> BookEntry {
+        BookEntry {
+            escrow: Pubkey::new_unique(),
+            deposit,
+            receive,
+        }
+    }
+
+    fn book() -> OfferBook {
+        OfferBook {
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            entries: vec![],
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_the_cheapest_offer_first() {
+        let mut book = book();
+        let (pricey, cheap, mid) = (entry(100, 300), entry(100, 100), entry(50, 100));
+        for listed in [pricey, cheap, mid] {
+            book.insert(listed).unwrap();
+        }
+        assert_eq!(book.entries, vec![cheap, mid, pricey]);
+    }
+
+    #[test]
+    fn equal_prices_stay_in_arrival_order() {
+        let mut book = book();
+        let (first, second) = (entry(100, 200), entry(10, 20));
+        book.insert(first).unwrap();
+        book.insert(second).unwrap();
+        assert_eq!(book.entries, vec![first, second]);
+    }
+
+    #[test]
+    fn a_full_book_drops_its_most_expensive_offer() {
+        let mut book = book();
+        for i in 0..MAX_BOOK_ENTRIES as u64 {
+            assert_eq!(book.insert(entry(1, 10 + i)).unwrap(), None);
+        }
+        let worst = *book.entries.last().unwrap();
+
+        assert!(book.insert(entry(1, 1_000)).is_err());
+
+        let best = entry(1, 1);
+        assert_eq!(book.insert(best).unwrap(), Some(worst.escrow));
+        assert_eq!(book.entries.len(), MAX_BOOK_ENTRIES);
+        assert_eq!(book.entries[0], best);
+        assert!(!book.entries.contains(&worst));
+    }
+
+    #[test]
+    fn only_whole_tokens_are_listed() {
+        assert!(OfferBook::check_listable(1, 0).is_ok());
+        assert!(OfferBook::check_listable(0, 0).is_err());
+        assert!(OfferBook::check_listable(999_999, 6).is_err());
+        assert!(OfferBook::check_listable(1_000_000, 6).is_ok());
+    }
+
+    #[test]
+    fn offers_shrunk_below_a_whole_token_leave_the_book() {
+        let mut book = book();
+        let (a, b) = (entry(2_000_000, 100), entry(1_000_000, 100));
+        book.insert(a).unwrap();
+        book.insert(b).unwrap();
+
+        assert!(!book.update(BookEntry { deposit: 999_999, ..a }, 6).unwrap());
+        assert_eq!(book.entries, vec![b]);
+    }
+
+    #[test]
+    fn updates_reposition_and_removals_drop_offers() {
+        let mut book = book();
+        let (a, b) = (entry(100, 100), entry(100, 200));
+        book.insert(a).unwrap();
+        book.insert(b).unwrap();
+
+        let repriced = BookEntry { receive: 300, ..a };
+        assert!(book.update(repriced, 0).unwrap());
+        assert_eq!(book.entries, vec![b, repriced]);
+
+        book.remove(&b.escrow);
+        book.remove(&Pubkey::new_unique());
+        assert_eq!(book.entries, vec![repriced]);
+    }
+}
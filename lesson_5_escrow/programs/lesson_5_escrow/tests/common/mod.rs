@@ -0,0 +1,319 @@
+// Shared LiteSVM setup. The tests load the program from target/deploy, so run `anchor build` first.
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        instruction::Instruction,
+        program_pack::Pack,
+    },
+    system_program, AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id, spl_associated_token_account},
+    token_2022::spl_token_2022::{
+        self,
+        extension::StateWithExtensions,
+        state::{Account as TokenAccount, Mint},
+    },
+};
+use litesvm::{types::TransactionResult, LiteSVM};
+use solana_account::Account;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_system_interface::instruction::create_account;
+use solana_transaction::Transaction;
+
+use lesson_5_escrow::{accounts, instruction, Escrow};
+
+pub use anchor_spl::{token::ID as TOKEN_PROGRAM_ID, token_2022::ID as TOKEN_2022_PROGRAM_ID};
+
+pub const PROGRAM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/lesson_5_escrow.so");
+
+pub const DEPOSIT: u64 = 100;
+pub const RECEIVE: u64 = 200;
+
+// every pairing of legacy and Token-2022 mints
+pub const TOKEN_PROGRAM_PAIRS: [(Pubkey, Pubkey); 4] = [
+    (TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID),
+    (TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID),
+    (TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID),
+    (TOKEN_2022_PROGRAM_ID, TOKEN_2022_PROGRAM_ID),
+];
+
+pub struct Pair {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+}
+
+pub struct Harness {
+    pub svm: LiteSVM,
+    pub maker: Keypair,
+    pub taker: Keypair,
+    pub treasury: Pubkey,
+}
+
+impl Harness {
+    /// Deploys the program with the maker as upgrade authority and sets up a zero-fee config,
+    /// which makes and takes require.
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let maker = Keypair::new();
+        deploy_upgradeable(&mut svm, &maker.pubkey());
+
+        let taker = Keypair::new();
+        for user in [&maker, &taker] {
+            svm.airdrop(&user.pubkey(), 10_000_000_000).unwrap();
+        }
+
+        let treasury = Pubkey::new_unique();
+        let ix = initialize_config_ix(&maker.pubkey(), &treasury);
+        send(&mut svm, &[ix], &[&maker]).unwrap();
+
+        Self { svm, maker, taker, treasury }
+    }
+
+    /// Mint A belongs to the maker and mint B to the taker, each funded with 10x what an offer moves.
+    pub fn pair(&mut self, token_program_a: Pubkey, token_program_b: Pubkey) -> Pair {
+        let mint_a = create_mint(&mut self.svm, &self.maker, token_program_a);
+        let mint_b = create_mint(&mut self.svm, &self.taker, token_program_b);
+
+        let maker_ata_a = create_ata(&mut self.svm, &self.maker, &self.maker.pubkey(), &mint_a, &token_program_a);
+        mint_to(&mut self.svm, &self.maker, &mint_a, &maker_ata_a, &token_program_a, 10 * DEPOSIT);
+
+        let taker_ata_b = create_ata(&mut self.svm, &self.taker, &self.taker.pubkey(), &mint_b, &token_program_b);
+        mint_to(&mut self.svm, &self.taker, &mint_b, &taker_ata_b, &token_program_b, 10 * RECEIVE);
+
+        Pair { mint_a, mint_b, token_program_a, token_program_b }
+    }
+
+    pub fn make(&mut self, pair: &Pair, seed: u64) -> TransactionResult {
+        let ix = make_ix(&self.maker.pubkey(), pair, seed);
+        send(&mut self.svm, &[ix], &[&self.maker])
+    }
+
+    pub fn take(&mut self, pair: &Pair, seed: u64) -> TransactionResult {
+        let ix = take_ix(&self.maker.pubkey(), &self.taker.pubkey(), &self.treasury, pair, seed);
+        send(&mut self.svm, &[ix], &[&self.taker])
+    }
+
+    pub fn refund(&mut self, pair: &Pair, seed: u64) -> TransactionResult {
+        let ix = refund_ix(&self.maker.pubkey(), pair, seed);
+        send(&mut self.svm, &[ix], &[&self.maker])
+    }
+
+    pub fn balance(&self, token_account: &Pubkey) -> u64 {
+        let account = self.svm.get_account(token_account).expect("token account exists");
+        StateWithExtensions::<TokenAccount>::unpack(&account.data).unwrap().base.amount
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_account(address).map_or(0, |account| account.lamports)
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.lamports(address) > 0
+    }
+
+    pub fn escrow(&self, address: &Pubkey) -> Escrow {
+        let account = self.svm.get_account(address).expect("escrow exists");
+        Escrow::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[lesson_5_escrow::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Writes the program and its program data account the way the upgradeable loader lays them out.
+/// `add_program_from_file` uses the non-upgradeable loader, which has no upgrade authority to check.
+pub fn deploy_upgradeable(svm: &mut LiteSVM, authority: &Pubkey) {
+    let elf = std::fs::read(PROGRAM_PATH).expect("run `anchor build` before the integration tests");
+
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let mut program_data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(*authority),
+    })
+    .unwrap();
+    program_data.resize(metadata_len, 0);
+    program_data.extend_from_slice(&elf);
+    let program = bincode::serialize(&UpgradeableLoaderState::Program {
+        programdata_address: program_data_address(),
+    })
+    .unwrap();
+
+    // the program data goes in first, since loading the program reads it
+    for (address, data, executable) in [
+        (program_data_address(), program_data, false),
+        (lesson_5_escrow::ID, program, true),
+    ] {
+        let account = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::ID,
+            executable,
+            rent_epoch: 0,
+        };
+        svm.set_account(address, account).unwrap();
+    }
+}
+
+/// Sends `ixs` with the first signer as fee payer.
+pub fn send(svm: &mut LiteSVM, ixs: &[Instruction], signers: &[&Keypair]) -> TransactionResult {
+    // a fresh blockhash, so sending the same instructions twice is not rejected as a duplicate
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signers[0].pubkey()), signers, svm.latest_blockhash());
+    svm.send_transaction(tx)
+}
+
+/// Asserts the transaction failed with the given Anchor error code, e.g. `ConstraintHasOne`.
+pub fn assert_error(result: TransactionResult, code: &str) {
+    let failed = result.expect_err("transaction should fail");
+    let expected = format!("Error Code: {code}");
+    assert!(
+        failed.meta.logs.iter().any(|log| log.contains(&expected)),
+        "expected {code}, got {:?}",
+        failed.meta.logs
+    );
+}
+
+pub fn create_mint(svm: &mut LiteSVM, authority: &Keypair, token_program: Pubkey) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = svm.minimum_balance_for_rent_exemption(Mint::LEN);
+    let ixs = [
+        create_account(&authority.pubkey(), &mint.pubkey(), rent, Mint::LEN as u64, &token_program),
+        spl_token_2022::instruction::initialize_mint2(&token_program, &mint.pubkey(), &authority.pubkey(), None, 0)
+            .unwrap(),
+    ];
+    send(svm, &ixs, &[authority, &mint]).unwrap();
+    mint.pubkey()
+}
+
+pub fn create_ata(svm: &mut LiteSVM, payer: &Keypair, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    let ix = spl_associated_token_account::instruction::create_associated_token_account(
+        &payer.pubkey(),
+        owner,
+        mint,
+        token_program,
+    );
+    send(svm, &[ix], &[payer]).unwrap();
+    ata(owner, mint, token_program)
+}
+
+pub fn mint_to(svm: &mut LiteSVM, authority: &Keypair, mint: &Pubkey, to: &Pubkey, token_program: &Pubkey, amount: u64) {
+    let ix = spl_token_2022::instruction::mint_to(token_program, mint, to, &authority.pubkey(), &[], amount).unwrap();
+    send(svm, &[ix], &[authority]).unwrap();
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+pub fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &lesson_5_escrow::ID).0
+}
+
+pub fn escrow_pda(maker: &Pubkey, seed: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow", maker.as_ref(), &seed.to_le_bytes()], &lesson_5_escrow::ID).0
+}
+
+pub fn vault(maker: &Pubkey, pair: &Pair, seed: u64) -> Pubkey {
+    ata(&escrow_pda(maker, seed), &pair.mint_a, &pair.token_program_a)
+}
+
+pub fn initialize_config_ix(admin: &Pubkey, treasury: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: lesson_5_escrow::ID,
+        accounts: accounts::InitializeConfig {
+            admin: *admin,
+            config: config_pda(),
+            program: lesson_5_escrow::ID,
+            program_data: program_data_address(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitializeConfig { fee_bps: 0, referral_bps: 0, fee_recipient: *treasury }.data(),
+    }
+}
+
+pub fn make_ix(maker: &Pubkey, pair: &Pair, seed: u64) -> Instruction {
+    Instruction {
+        program_id: lesson_5_escrow::ID,
+        accounts: accounts::Make {
+            maker: *maker,
+            mint_a: pair.mint_a,
+            mint_b: pair.mint_b,
+            maker_ata_a: ata(maker, &pair.mint_a, &pair.token_program_a),
+            escrow: escrow_pda(maker, seed),
+            vault: vault(maker, pair, seed),
+            offer_book: None,
+            evicted_escrow: None,
+            config: config_pda(),
+            token_program_a: pair.token_program_a,
+            token_program_b: pair.token_program_b,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Make {
+            seed,
+            deposit: DEPOSIT,
+            receive: RECEIVE,
+            expires_at: 0,
+            taker: None,
+            net_receive: false,
+        }
+        .data(),
+    }
+}
+
+pub fn take_ix(maker: &Pubkey, taker: &Pubkey, treasury: &Pubkey, pair: &Pair, seed: u64) -> Instruction {
+    Instruction {
+        program_id: lesson_5_escrow::ID,
+        accounts: accounts::Take {
+            maker: *maker,
+            taker: *taker,
+            mint_a: pair.mint_a,
+            mint_b: pair.mint_b,
+            maker_ata_b: ata(maker, &pair.mint_b, &pair.token_program_b),
+            taker_ata_a: ata(taker, &pair.mint_a, &pair.token_program_a),
+            taker_ata_b: ata(taker, &pair.mint_b, &pair.token_program_b),
+            escrow: escrow_pda(maker, seed),
+            vault: vault(maker, pair, seed),
+            offer_book: None,
+            config: config_pda(),
+            fee_recipient: *treasury,
+            treasury_ata_b: ata(treasury, &pair.mint_b, &pair.token_program_b),
+            referrer: None,
+            referrer_ata_b: None,
+            token_program_a: pair.token_program_a,
+            token_program_b: pair.token_program_b,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Take { expected_receive: RECEIVE, min_deposit: DEPOSIT }.data(),
+    }
+}
+
+pub fn refund_ix(maker: &Pubkey, pair: &Pair, seed: u64) -> Instruction {
+    Instruction {
+        program_id: lesson_5_escrow::ID,
+        accounts: accounts::Refund {
+            maker: *maker,
+            mint_a: pair.mint_a,
+            maker_ata_a: ata(maker, &pair.mint_a, &pair.token_program_a),
+            escrow: escrow_pda(maker, seed),
+            vault: vault(maker, pair, seed),
+            offer_book: None,
+            token_program_a: pair.token_program_a,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Refund {}.data(),
+    }
+}
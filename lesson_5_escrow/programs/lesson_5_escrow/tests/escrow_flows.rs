@@ -0,0 +1,179 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction, system_program, InstructionData, ToAccountMetas};
+use common::*;
+use litesvm::LiteSVM;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+use lesson_5_escrow::{accounts, instruction};
+
+const SEED: u64 = 7;
+
+fn setup() -> (Harness, Pair) {
+    let mut h = Harness::new();
+    let pair = h.pair(TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID);
+    (h, pair)
+}
+
+#[test]
+fn make_locks_the_deposit() {
+    let (mut h, pair) = setup();
+    let maker = h.maker.pubkey();
+
+    h.make(&pair, SEED).unwrap();
+
+    let escrow = h.escrow(&escrow_pda(&maker, SEED));
+    assert_eq!((escrow.maker, escrow.seed), (maker, SEED));
+    assert_eq!((escrow.deposit, escrow.receive), (DEPOSIT, RECEIVE));
+    assert_eq!(h.balance(&vault(&maker, &pair, SEED)), DEPOSIT);
+    assert_eq!(h.balance(&ata(&maker, &pair.mint_a, &pair.token_program_a)), 9 * DEPOSIT);
+}
+
+#[test]
+fn take_swaps_both_sides_and_returns_the_rent_to_the_maker() {
+    let (mut h, pair) = setup();
+    let maker = h.maker.pubkey();
+    let taker = h.taker.pubkey();
+    let escrow = escrow_pda(&maker, SEED);
+    let vault = vault(&maker, &pair, SEED);
+
+    h.make(&pair, SEED).unwrap();
+    let rent = h.lamports(&escrow) + h.lamports(&vault);
+    let initial_maker_lamports = h.lamports(&maker);
+
+    // the taker signs and pays the fees, so the maker's lamports move by the rent alone
+    h.take(&pair, SEED).unwrap();
+
+    assert_eq!(h.balance(&ata(&taker, &pair.mint_a, &pair.token_program_a)), DEPOSIT);
+    assert_eq!(h.balance(&ata(&taker, &pair.mint_b, &pair.token_program_b)), 9 * RECEIVE);
+    assert_eq!(h.balance(&ata(&maker, &pair.mint_b, &pair.token_program_b)), RECEIVE);
+    assert_eq!(h.lamports(&maker) - initial_maker_lamports, rent);
+    assert!(!h.exists(&escrow));
+    assert!(!h.exists(&vault));
+}
+
+#[test]
+fn refund_returns_the_deposit_and_the_rent_to_the_maker() {
+    let (mut h, pair) = setup();
+    let maker = h.maker.pubkey();
+    let escrow = escrow_pda(&maker, SEED);
+    let vault = vault(&maker, &pair, SEED);
+
+    h.make(&pair, SEED).unwrap();
+    let rent = h.lamports(&escrow) + h.lamports(&vault);
+    let initial_maker_lamports = h.lamports(&maker);
+
+    // someone else pays the fee, so only the reclaimed rent shows up
+    let ix = refund_ix(&maker, &pair, SEED);
+    send(&mut h.svm, &[ix], &[&h.taker, &h.maker]).unwrap();
+
+    assert_eq!(h.balance(&ata(&maker, &pair.mint_a, &pair.token_program_a)), 10 * DEPOSIT);
+    assert_eq!(h.lamports(&maker) - initial_maker_lamports, rent);
+    assert!(!h.exists(&escrow));
+    assert!(!h.exists(&vault));
+}
+
+#[test]
+fn only_the_maker_can_refund() {
+    let (mut h, pair) = setup();
+    let maker = h.maker.pubkey();
+    let impostor = h.taker.pubkey();
+    let escrow = escrow_pda(&maker, SEED);
+    let vault = vault(&maker, &pair, SEED);
+
+    h.make(&pair, SEED).unwrap();
+    let impostor_ata_a = create_ata(&mut h.svm, &h.taker, &impostor, &pair.mint_a, &pair.token_program_a);
+
+    // the impostor signs as "maker" against someone else's escrow. The escrow's address is derived
+    // from the maker, so it fails the seeds check before has_one is reached
+    let ix = Instruction {
+        program_id: lesson_5_escrow::ID,
+        accounts: accounts::Refund {
+            maker: impostor,
+            mint_a: pair.mint_a,
+            maker_ata_a: impostor_ata_a,
+            escrow,
+            vault,
+            offer_book: None,
+            token_program_a: pair.token_program_a,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Refund {}.data(),
+    };
+    assert_error(send(&mut h.svm, &[ix], &[&h.taker]), "ConstraintSeeds");
+
+    assert_eq!(h.balance(&vault), DEPOSIT);
+    assert_eq!(h.balance(&impostor_ata_a), 0);
+    assert!(h.exists(&escrow));
+}
+
+#[test]
+fn a_seed_cannot_be_reused_while_its_offer_is_open() {
+    let (mut h, pair) = setup();
+    let maker = h.maker.pubkey();
+
+    h.make(&pair, SEED).unwrap();
+    let failed = h.make(&pair, SEED).expect_err("the seed is taken");
+    assert!(
+        failed.meta.logs.iter().any(|log| log.contains("already in use")),
+        "expected the escrow to be in use, got {:?}",
+        failed.meta.logs
+    );
+
+    // the first offer is untouched
+    assert_eq!(h.balance(&vault(&maker, &pair, SEED)), DEPOSIT);
+    assert_eq!(h.balance(&ata(&maker, &pair.mint_a, &pair.token_program_a)), 9 * DEPOSIT);
+}
+
+#[test]
+fn a_seed_can_be_reused_once_its_offer_is_closed() {
+    let (mut h, pair) = setup();
+    let maker = h.maker.pubkey();
+
+    h.make(&pair, SEED).unwrap();
+    h.refund(&pair, SEED).unwrap();
+    h.make(&pair, SEED).unwrap();
+    h.take(&pair, SEED).unwrap();
+    h.make(&pair, SEED).unwrap();
+
+    assert_eq!(h.escrow(&escrow_pda(&maker, SEED)).deposit, DEPOSIT);
+    assert_eq!(h.balance(&ata(&maker, &pair.mint_a, &pair.token_program_a)), 8 * DEPOSIT);
+}
+
+#[test]
+fn seeds_are_scoped_to_the_maker() {
+    let (mut h, pair) = setup();
+    let other_maker = Keypair::new();
+    h.svm.airdrop(&other_maker.pubkey(), 10_000_000_000).unwrap();
+
+    let other_ata_a = create_ata(&mut h.svm, &other_maker, &other_maker.pubkey(), &pair.mint_a, &pair.token_program_a);
+    mint_to(&mut h.svm, &h.maker, &pair.mint_a, &other_ata_a, &pair.token_program_a, DEPOSIT);
+
+    h.make(&pair, SEED).unwrap();
+    let ix = make_ix(&other_maker.pubkey(), &pair, SEED);
+    send(&mut h.svm, &[ix], &[&other_maker]).unwrap();
+
+    assert_ne!(escrow_pda(&h.maker.pubkey(), SEED), escrow_pda(&other_maker.pubkey(), SEED));
+    assert_eq!(h.balance(&vault(&h.maker.pubkey(), &pair, SEED)), DEPOSIT);
+    assert_eq!(h.balance(&vault(&other_maker.pubkey(), &pair, SEED)), DEPOSIT);
+}
+
+#[test]
+fn only_the_upgrade_authority_can_set_up_the_config() {
+    let mut svm = LiteSVM::new();
+    let authority = Keypair::new();
+    let impostor = Keypair::new();
+    deploy_upgradeable(&mut svm, &authority.pubkey());
+    for user in [&authority, &impostor] {
+        svm.airdrop(&user.pubkey(), 10_000_000_000).unwrap();
+    }
+
+    let treasury = Pubkey::new_unique();
+    let ix = initialize_config_ix(&impostor.pubkey(), &treasury);
+    assert_error(send(&mut svm, &[ix], &[&impostor]), "NotUpgradeAuthority");
+
+    let ix = initialize_config_ix(&authority.pubkey(), &treasury);
+    send(&mut svm, &[ix], &[&authority]).unwrap();
+}
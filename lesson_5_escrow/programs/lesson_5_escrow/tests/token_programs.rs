@@ -0,0 +1,51 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use solana_signer::Signer;
+
+const SEED: u64 = 42;
+
+fn label(program_a: &Pubkey, program_b: &Pubkey) -> String {
+    let name = |program: &Pubkey| if *program == TOKEN_PROGRAM_ID { "spl-token" } else { "token-2022" };
+    format!("{} / {}", name(program_a), name(program_b))
+}
+
+#[test]
+fn make_and_take_with_every_token_program_pair() {
+    for (program_a, program_b) in TOKEN_PROGRAM_PAIRS {
+        let pairing = label(&program_a, &program_b);
+        let mut h = Harness::new();
+        let pair = h.pair(program_a, program_b);
+        let maker = h.maker.pubkey();
+        let taker = h.taker.pubkey();
+
+        h.make(&pair, SEED).unwrap_or_else(|e| panic!("make {pairing}: {:?}", e.meta.logs));
+        assert_eq!(h.balance(&vault(&maker, &pair, SEED)), DEPOSIT, "{pairing}");
+
+        h.take(&pair, SEED).unwrap_or_else(|e| panic!("take {pairing}: {:?}", e.meta.logs));
+        assert_eq!(h.balance(&ata(&taker, &pair.mint_a, &program_a)), DEPOSIT, "{pairing}");
+        assert_eq!(h.balance(&ata(&maker, &pair.mint_b, &program_b)), RECEIVE, "{pairing}");
+        assert!(!h.exists(&escrow_pda(&maker, SEED)), "{pairing}");
+        assert!(!h.exists(&vault(&maker, &pair, SEED)), "{pairing}");
+    }
+}
+
+#[test]
+fn make_and_refund_with_every_token_program_pair() {
+    for (program_a, program_b) in TOKEN_PROGRAM_PAIRS {
+        let pairing = label(&program_a, &program_b);
+        let mut h = Harness::new();
+        let pair = h.pair(program_a, program_b);
+        let maker = h.maker.pubkey();
+        let maker_ata_a = ata(&maker, &pair.mint_a, &program_a);
+
+        h.make(&pair, SEED).unwrap_or_else(|e| panic!("make {pairing}: {:?}", e.meta.logs));
+        assert_eq!(h.balance(&maker_ata_a), 9 * DEPOSIT, "{pairing}");
+
+        h.refund(&pair, SEED).unwrap_or_else(|e| panic!("refund {pairing}: {:?}", e.meta.logs));
+        assert_eq!(h.balance(&maker_ata_a), 10 * DEPOSIT, "{pairing}");
+        assert!(!h.exists(&escrow_pda(&maker, SEED)), "{pairing}");
+        assert!(!h.exists(&vault(&maker, &pair, SEED)), "{pairing}");
+    }
+}
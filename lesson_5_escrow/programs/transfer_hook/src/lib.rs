@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+declare_id!("DqEN4fU9GZLkbasuqvDToRKwi7EnN2JGcyeaJLbaJkZC");
+
+// Test fixture for the escrow: a transfer hook that counts the transfers of a mint.
+// The counter is an extra account, so a transfer only goes through if the caller
+// forwarded the hook's accounts.
+#[program]
+pub mod transfer_hook {
+    use super::*;
+
+    pub fn initialize_extra_account_meta_list(ctx: Context<InitializeExtraAccountMetaList>) -> Result<()> {
+        let counter = ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal { bytes: b"counter".to_vec() },
+                Seed::AccountKey { index: 1 }, // the mint
+            ],
+            false,
+            true,
+        )?;
+
+        let mut data = ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?;
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &[counter])?;
+
+        ctx.accounts.counter.set_inner(TransferCounter {
+            transfers: 0,
+            bump: ctx.bumps.counter,
+        });
+        Ok(())
+    }
+
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        ctx.accounts.counter.transfers += 1;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: only its key is used, as a seed
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: written with the TLV layout the transfer hook interface expects
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        space = ExtraAccountMetaList::size_of(1)?,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"counter", mint.key().as_ref()],
+        bump,
+        space = TransferCounter::DISCRIMINATOR.len() + TransferCounter::INIT_SPACE,
+    )]
+    pub counter: Account<'info, TransferCounter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// account order is fixed by the transfer hook interface
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    /// CHECK: source token account
+    pub source: UncheckedAccount<'info>,
+    /// CHECK: mint
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: destination token account
+    pub destination: UncheckedAccount<'info>,
+    /// CHECK: source owner or delegate
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: validated by its seeds
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"counter", mint.key().as_ref()],
+        bump = counter.bump,
+    )]
+    pub counter: Account<'info, TransferCounter>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TransferCounter {
+    pub transfers: u64,
+    pub bump: u8,
+}
@@ -1,7 +1,11 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Lesson5Escrow } from "../target/types/lesson_5_escrow";
-import { ASSOCIATED_TOKEN_PROGRAM_ID, createAssociatedTokenAccountInstruction, createMint, getAssociatedTokenAddressSync, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { TransferHook } from "../target/types/transfer_hook";
+import { ASSOCIATED_TOKEN_PROGRAM_ID, createAssociatedTokenAccountInstruction, createInitializeMintInstruction, createInitializeTransferFeeConfigInstruction, createInitializeTransferHookInstruction, createMint, ExtensionType, getAssociatedTokenAddressSync, getMintLen, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { create, fetchAssetV1, mplCore, MPL_CORE_PROGRAM_ID } from "@metaplex-foundation/mpl-core";
+import { generateSigner, keypairIdentity, publicKey as pk } from "@metaplex-foundation/umi";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
 import { expect } from "chai";
 
 const airdropAndMint = async ({
@@ -35,6 +39,15 @@ const airdropAndMint = async ({
       };
 };
 
+const expectError = async (promise: Promise<unknown>, code: string) => {
+  try {
+    await promise;
+    expect.fail(`expected ${code}`);
+  } catch (err) {
+    expect((err as anchor.AnchorError).error?.errorCode?.code).to.equal(code);
+  }
+};
+
 describe("lesson_5_escrow", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -60,6 +73,31 @@ describe("lesson_5_escrow", () => {
   const depositAmount = 100;
   const receiveAmount = 200;
 
+  // the terms a taker read before taking, so an offer amended in between is refused
+  const quoteOf = async (escrow: anchor.web3.PublicKey) => {
+    const { receive, deposit } = await program.account.escrow.fetch(escrow);
+    return [receive, deposit] as const;
+  };
+
+  const treasury = anchor.web3.Keypair.generate();
+  // anchor test deploys with the provider wallet as upgrade authority
+  const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+  const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const feeAccounts = (referrer: anchor.web3.PublicKey | null = null) => ({
+    config: configPda,
+    feeRecipient: treasury.publicKey,
+    treasuryAtaB: getAssociatedTokenAddressSync(mintB, treasury.publicKey),
+    referrer,
+    referrerAtaB: referrer && getAssociatedTokenAddressSync(mintB, referrer),
+  });
+
   before(async () => {
 
     const promises = [];
@@ -71,7 +109,7 @@ describe("lesson_5_escrow", () => {
         provider,
         authority: obj.authority,
         ata: obj.ata,
-        amount: 2 * obj.amount,
+        amount: 5 * obj.amount,
       }));
     }
     const results = await Promise.all(promises);
@@ -79,6 +117,18 @@ describe("lesson_5_escrow", () => {
     makerAtaA = results[0].ata;
     mintB = results[1].mint;
     takerAtaB = results[1].ata;
+
+    // no fee until the fee tests turn it on
+    await program.methods
+      .initializeConfig(0, 0, treasury.publicKey)
+      .accountsStrict({
+        admin: maker,
+        config: configPda,
+        program: program.programId,
+        programData,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
   });
 
   describe("after initializing the vault", async () => {
@@ -89,7 +139,7 @@ describe("lesson_5_escrow", () => {
       );
       vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
 
-      await program.methods.make(seed,  new anchor.BN(depositAmount), new anchor.BN(receiveAmount))
+      await program.methods.make(seed,  new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(0), null, false)
         .accountsStrict({
           maker,
           mintA,
@@ -97,6 +147,9 @@ describe("lesson_5_escrow", () => {
           makerAtaA,
           escrow: escrowPda,
           vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenProgramA: TOKEN_PROGRAM_ID,
           tokenProgramB: TOKEN_PROGRAM_ID,
@@ -110,7 +163,10 @@ describe("lesson_5_escrow", () => {
       expect(escrowAccount.seed.toString()).to.equal(seed.toString());
       expect(escrowAccount.maker.toBase58()).to.equal(maker.toBase58());
       expect(escrowAccount.mintA.toBase58()).to.equal(mintA.toBase58());
+      expect(escrowAccount.deposit.toNumber()).to.equal(depositAmount);
       expect(escrowAccount.receive.toNumber()).to.equal(receiveAmount);
+      expect(escrowAccount.expiresAt.toNumber()).to.equal(0);
+      expect(escrowAccount.taker).to.be.null;
       expect(escrowAccount.bump).to.equal(escrowBump);
     });
 
@@ -131,6 +187,7 @@ describe("lesson_5_escrow", () => {
           makerAtaA: makerAtaA,
           escrow: escrowPda,
           vault: vault,
+          offerBook: null,
           tokenProgramA: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -156,7 +213,7 @@ describe("lesson_5_escrow", () => {
 
       // Make (again for take path)
       await program.methods
-        .make(seed2, new anchor.BN(depositAmount), new anchor.BN(receiveAmount))
+        .make(seed2, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(0), null, false)
         .accountsStrict({
           maker: maker,
           mintA: mintA,
@@ -164,6 +221,9 @@ describe("lesson_5_escrow", () => {
           makerAtaA: makerAtaA,
           escrow: escrowPda,
           vault: vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenProgramA: TOKEN_PROGRAM_ID,
           tokenProgramB: TOKEN_PROGRAM_ID,
@@ -178,7 +238,7 @@ describe("lesson_5_escrow", () => {
 
       // Take
       await program.methods
-        .take()
+        .take(...(await quoteOf(escrowPda)))
         .accountsStrict({
           taker: taker.publicKey,
           maker: maker,
@@ -189,6 +249,8 @@ describe("lesson_5_escrow", () => {
           takerAtaB: takerAtaB,
           escrow: escrowPda,
           vault: vault,
+          offerBook: null,
+          ...feeAccounts(),
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenProgramA: TOKEN_PROGRAM_ID,
           tokenProgramB: TOKEN_PROGRAM_ID,
@@ -220,4 +282,1722 @@ describe("lesson_5_escrow", () => {
     });
   });
 
+  describe("when taking the escrow in several fills", async () => {
+    const seed3 = new anchor.BN(3333);
+
+    const takePartial = async (amountB: number) => program.methods
+      .takePartial(new anchor.BN(amountB), ...(await quoteOf(escrowPda)))
+      .accountsStrict({
+        taker: taker.publicKey,
+        maker,
+        mintA,
+        mintB,
+        makerAtaB,
+        takerAtaA,
+        takerAtaB,
+        escrow: escrowPda,
+        vault,
+        offerBook: null,
+        ...feeAccounts(),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+
+    const balance = async (ata: anchor.web3.PublicKey) =>
+      (await provider.connection.getTokenAccountBalance(ata)).value.uiAmount;
+
+    before(async () => {
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed3.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await program.methods
+        .make(seed3, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("it pays out token A pro-rata and keeps the offer open", async () => {
+      const initialTakerA = await balance(takerAtaA);
+
+      await takePartial(receiveAmount / 4);
+
+      expect(await balance(takerAtaA) - initialTakerA).to.equal(depositAmount / 4);
+
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      expect(escrowAccount.deposit.toNumber()).to.equal(depositAmount * 3 / 4);
+      expect(escrowAccount.receive.toNumber()).to.equal(receiveAmount * 3 / 4);
+    });
+
+    it("it rounds down in favour of the maker", async () => {
+      // 3 B is worth 1.5 A at this price
+      const initialTakerA = await balance(takerAtaA);
+
+      await takePartial(3);
+
+      expect(await balance(takerAtaA) - initialTakerA).to.equal(1);
+
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      expect(escrowAccount.deposit.toNumber()).to.equal(depositAmount * 3 / 4 - 1);
+    });
+
+    it("it refuses fills that pay out nothing", async () => {
+      await expectError(takePartial(1), "FillTooSmall");
+    });
+
+    it("it refuses fills larger than the remaining offer", async () => {
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      await expectError(takePartial(escrowAccount.receive.toNumber() + 1), "FillExceedsOffer");
+    });
+
+    it("the last fill takes the rest and closes the escrow and vault", async () => {
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      const initialTakerA = await balance(takerAtaA);
+
+      await takePartial(escrowAccount.receive.toNumber());
+
+      expect(await balance(takerAtaA) - initialTakerA).to.equal(escrowAccount.deposit.toNumber());
+      expect(await provider.connection.getAccountInfo(escrowPda)).to.be.null;
+      expect(await provider.connection.getAccountInfo(vault)).to.be.null;
+    });
+  });
+
+  describe("when the escrow expires", async () => {
+    const seed4 = new anchor.BN(4444);
+    const cranker = anchor.web3.Keypair.generate();
+
+    const take = async () => program.methods
+      .take(...(await quoteOf(escrowPda)))
+      .accountsStrict({
+        taker: taker.publicKey,
+        maker,
+        mintA,
+        mintB,
+        makerAtaB,
+        takerAtaA,
+        takerAtaB,
+        escrow: escrowPda,
+        vault,
+        offerBook: null,
+        ...feeAccounts(),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+
+    const cleanupExpired = () => program.methods
+      .cleanupExpired()
+      .accountsStrict({
+        payer: cranker.publicKey,
+        maker,
+        mintA,
+        makerAtaA,
+        escrow: escrowPda,
+        vault,
+        offerBook: null,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([cranker])
+      .rpc();
+
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+    before(async () => {
+      const signature = await provider.connection.requestAirdrop(cranker.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction({ signature, ...(await provider.connection.getLatestBlockhash()) }, "confirmed");
+
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed4.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      const now = await provider.connection.getBlockTime(await provider.connection.getSlot());
+      await program.methods
+        .make(seed4, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(now + 3), null, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("it cannot be cleaned up before it expires", async () => {
+      await expectError(cleanupExpired(), "OfferNotExpired");
+    });
+
+    it("it cannot be taken once expired", async () => {
+      await sleep(5000);
+      await expectError(take(), "OfferExpired");
+    });
+
+    it("anyone can return the tokens and rent to the maker", async () => {
+      const initialMakerA = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      const initialMakerLamports = await provider.connection.getBalance(maker);
+      const rent = await provider.connection.getBalance(escrowPda) + await provider.connection.getBalance(vault);
+
+      await cleanupExpired();
+
+      const finalMakerA = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      expect(finalMakerA - initialMakerA).to.equal(depositAmount);
+      expect(await provider.connection.getBalance(maker) - initialMakerLamports).to.equal(rent);
+      expect(await provider.connection.getAccountInfo(escrowPda)).to.be.null;
+      expect(await provider.connection.getAccountInfo(vault)).to.be.null;
+    });
+  });
+
+  describe("when the escrow is reserved for a taker", async () => {
+    const seed5 = new anchor.BN(5555);
+    const desk = anchor.web3.Keypair.generate();
+
+    const take = async () => program.methods
+      .take(...(await quoteOf(escrowPda)))
+      .accountsStrict({
+        taker: taker.publicKey,
+        maker,
+        mintA,
+        mintB,
+        makerAtaB,
+        takerAtaA,
+        takerAtaB,
+        escrow: escrowPda,
+        vault,
+        offerBook: null,
+        ...feeAccounts(),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+
+    const setTaker = (designated: anchor.web3.PublicKey | null) => program.methods
+      .setTaker(designated)
+      .accountsStrict({ maker, escrow: escrowPda })
+      .rpc();
+
+    before(async () => {
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed5.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await program.methods
+        .make(seed5, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(0), desk.publicKey, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("it stores the designated taker", async () => {
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      expect(escrowAccount.taker.toBase58()).to.equal(desk.publicKey.toBase58());
+    });
+
+    it("other takers are refused", async () => {
+      await expectError(take(), "NotDesignatedTaker");
+    });
+
+    it("the maker can clear the designated taker", async () => {
+      await setTaker(null);
+
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      expect(escrowAccount.taker).to.be.null;
+    });
+
+    it("the maker can rotate the designated taker without cancelling", async () => {
+      await setTaker(taker.publicKey);
+      await take();
+
+      expect(await provider.connection.getAccountInfo(escrowPda)).to.be.null;
+    });
+  });
+
+  describe("when the maker amends the escrow", async () => {
+    const seed6 = new anchor.BN(6666);
+
+    const updateOffer = (newReceive: number, delta: number) => program.methods
+      .updateOffer(new anchor.BN(newReceive), new anchor.BN(delta))
+      .accountsStrict({
+        maker,
+        mintA,
+        makerAtaA,
+        escrow: escrowPda,
+        vault,
+        offerBook: null,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const vaultBalance = async () => (await provider.connection.getTokenAccountBalance(vault)).value.uiAmount;
+
+    before(async () => {
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed6.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await program.methods
+        .make(seed6, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("it tops up the vault, reprices and emits an event", async () => {
+      const signature = await updateOffer(300, 50);
+
+      expect(await vaultBalance()).to.equal(depositAmount + 50);
+
+      const escrowAccount = await program.account.escrow.fetch(escrowPda);
+      expect(escrowAccount.deposit.toNumber()).to.equal(depositAmount + 50);
+      expect(escrowAccount.receive.toNumber()).to.equal(300);
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const [event] = [...parser.parseLogs(tx.meta.logMessages)];
+      expect(event.name).to.equal("offerUpdated");
+      expect(event.data.escrow.toBase58()).to.equal(escrowPda.toBase58());
+      expect(event.data.deposit.toNumber()).to.equal(depositAmount + 50);
+      expect(event.data.receive.toNumber()).to.equal(300);
+    });
+
+    it("it withdraws token A back to the maker", async () => {
+      await updateOffer(100, -120);
+
+      expect(await vaultBalance()).to.equal(depositAmount + 50 - 120);
+    });
+
+    it("it refuses to empty the vault", async () => {
+      await expectError(updateOffer(100, -(depositAmount + 50 - 120)), "EmptyOffer");
+    });
+
+    it("it refuses a take quoted before the offer was amended", async () => {
+      const stale = [new anchor.BN(receiveAmount), new anchor.BN(depositAmount)] as const;
+      await expectError(
+        program.methods
+          .take(...stale)
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker,
+            mintA,
+            mintB,
+            makerAtaB,
+            takerAtaA,
+            takerAtaB,
+            escrow: escrowPda,
+            vault,
+            offerBook: null,
+            ...feeAccounts(),
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgramA: TOKEN_PROGRAM_ID,
+            tokenProgramB: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc(),
+        "SlippageExceeded"
+      );
+    });
+  });
+
+  describe("when trading a basket of tokens", async () => {
+    const basketSeed = new anchor.BN(7777);
+    const refundSeed = new anchor.BN(8888);
+
+    // the maker adds a legacy mint C to token A, the taker adds a Token-2022 mint D to token B
+    let mintC: anchor.web3.PublicKey;
+    let mintD: anchor.web3.PublicKey;
+
+    const give = () => [
+      { mint: mintA, amount: new anchor.BN(10), tokenProgram: TOKEN_PROGRAM_ID },
+      { mint: mintC, amount: new anchor.BN(20), tokenProgram: TOKEN_PROGRAM_ID },
+    ];
+    const receive = () => [
+      { mint: mintB, amount: new anchor.BN(30), tokenProgram: TOKEN_PROGRAM_ID },
+      { mint: mintD, amount: new anchor.BN(40), tokenProgram: TOKEN_2022_PROGRAM_ID },
+    ];
+
+    const ata = (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, tokenProgram: anchor.web3.PublicKey) =>
+      getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+
+    const legAccounts = (legs: { mint: anchor.web3.PublicKey, tokenProgram: anchor.web3.PublicKey }[], from: anchor.web3.PublicKey, to: anchor.web3.PublicKey) =>
+      legs.flatMap(({ mint, tokenProgram }) => [
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: ata(mint, from, tokenProgram), isSigner: false, isWritable: true },
+        { pubkey: ata(mint, to, tokenProgram), isSigner: false, isWritable: true },
+        { pubkey: tokenProgram, isSigner: false, isWritable: false },
+      ]);
+
+    const deriveBasket = (basketSeed: anchor.BN) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("basket"), maker.toBuffer(), basketSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+    const makeBasket = (basketSeed: anchor.BN) => program.methods
+      .makeBasket(
+        basketSeed,
+        give().map(({ mint, amount }) => ({ mint, amount })),
+        receive().map(({ mint, amount }) => ({ mint, amount }))
+      )
+      .accountsStrict({
+        maker,
+        basket: deriveBasket(basketSeed),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(legAccounts(give(), maker, deriveBasket(basketSeed)))
+      .rpc();
+
+    const takeBasket = (remainingAccounts: anchor.web3.AccountMeta[]) => program.methods
+      .takeBasket()
+      .accountsStrict({
+        maker,
+        taker: taker.publicKey,
+        basket: deriveBasket(basketSeed),
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([taker])
+      .rpc();
+
+    const balance = async (address: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(address)).value.amount);
+
+    before(async () => {
+      mintC = await createMint(provider.connection, provider.wallet.payer, maker, null, 0);
+      const makerAtaC = await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, mintC, maker);
+      await mintTo(provider.connection, provider.wallet.payer, mintC, makerAtaC.address, maker, 100);
+
+      mintD = await createMint(provider.connection, taker, taker.publicKey, null, 0, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+      const takerAtaD = await getOrCreateAssociatedTokenAccount(provider.connection, taker, mintD, taker.publicKey, false, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+      await mintTo(provider.connection, taker, mintD, takerAtaD.address, taker, 100, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      await makeBasket(basketSeed);
+    });
+
+    it("it locks every token the maker gives in its own vault", async () => {
+      const basket = deriveBasket(basketSeed);
+      for (const { mint, amount, tokenProgram } of give()) {
+        expect(await balance(ata(mint, basket, tokenProgram))).to.equal(amount.toNumber());
+      }
+
+      const basketAccount = await program.account.basketEscrow.fetch(basket);
+      expect(basketAccount.give.map((leg) => leg.mint.toBase58())).to.deep.equal(give().map(({ mint }) => mint.toBase58()));
+      expect(basketAccount.receive.map((leg) => leg.amount.toNumber())).to.deep.equal([30, 40]);
+    });
+
+    it("it refuses accounts that do not match the legs", async () => {
+      const basket = deriveBasket(basketSeed);
+      const swapped = [
+        ...legAccounts(receive().reverse(), taker.publicKey, maker),
+        ...legAccounts(give(), basket, taker.publicKey),
+      ];
+      await expectError(takeBasket(swapped), "LegAccountMismatch");
+    });
+
+    it("it settles every leg at once and closes the basket", async () => {
+      const basket = deriveBasket(basketSeed);
+      const before = await Promise.all([
+        ...give().map(({ mint, tokenProgram }) => balance(ata(mint, taker.publicKey, tokenProgram)).catch(() => 0)),
+        ...receive().map(({ mint, tokenProgram }) => balance(ata(mint, maker, tokenProgram)).catch(() => 0)),
+      ]);
+
+      await takeBasket([
+        ...legAccounts(receive(), taker.publicKey, maker),
+        ...legAccounts(give(), basket, taker.publicKey),
+      ]);
+
+      const after = await Promise.all([
+        ...give().map(({ mint, tokenProgram }) => balance(ata(mint, taker.publicKey, tokenProgram))),
+        ...receive().map(({ mint, tokenProgram }) => balance(ata(mint, maker, tokenProgram))),
+      ]);
+      expect(after.map((amount, i) => amount - before[i])).to.deep.equal([10, 20, 30, 40]);
+
+      expect(await provider.connection.getAccountInfo(basket)).to.be.null;
+      for (const { mint, tokenProgram } of give()) {
+        expect(await provider.connection.getAccountInfo(ata(mint, basket, tokenProgram))).to.be.null;
+      }
+    });
+
+    it("the maker can refund a basket", async () => {
+      const basket = deriveBasket(refundSeed);
+      await makeBasket(refundSeed);
+      const before = await Promise.all(give().map(({ mint, tokenProgram }) => balance(ata(mint, maker, tokenProgram))));
+
+      await program.methods
+        .refundBasket()
+        .accountsStrict({
+          maker,
+          basket,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(legAccounts(give(), basket, maker))
+        .rpc();
+
+      const after = await Promise.all(give().map(({ mint, tokenProgram }) => balance(ata(mint, maker, tokenProgram))));
+      expect(after.map((amount, i) => amount - before[i])).to.deep.equal([10, 20]);
+      expect(await provider.connection.getAccountInfo(basket)).to.be.null;
+    });
+  });
+
+  describe("when trading Core assets", async () => {
+    const umi = createUmi(provider.connection.rpcEndpoint).use(mplCore());
+    umi.use(keypairIdentity(umi.eddsa.createKeypairFromSecretKey(provider.wallet.payer.secretKey)));
+
+    const createAsset = async (owner: anchor.web3.PublicKey) => {
+      const asset = generateSigner(umi);
+      await create(umi, { asset, name: "Escrowed", uri: "https://example.com/asset.json", owner: pk(owner.toBase58()) })
+        .sendAndConfirm(umi);
+      return new anchor.web3.PublicKey(asset.publicKey);
+    };
+
+    const ownerOf = async (asset: anchor.web3.PublicKey) =>
+      (await fetchAssetV1(umi, pk(asset.toBase58()))).owner.toString();
+
+    const deriveAssetEscrow = (seed: anchor.BN) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("asset_escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+    // accounts of a leg that is not used are passed as null
+    const noTokenA = { mintA: null, makerAtaA: null, vault: null, tokenProgramA: null };
+
+    const makeAssetOffer = (seed: anchor.BN, give: any, receive: any, accounts: any) => program.methods
+      .makeAssetOffer(seed, give, receive)
+      .accountsStrict({
+        maker,
+        escrow: deriveAssetEscrow(seed),
+        giveAsset: null,
+        giveCollection: null,
+        ...noTokenA,
+        coreProgram: MPL_CORE_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...accounts,
+      })
+      .rpc();
+
+    const takeAssetOffer = (seed: anchor.BN, accounts: any) => program.methods
+      .takeAssetOffer()
+      .accountsStrict({
+        maker,
+        taker: taker.publicKey,
+        escrow: deriveAssetEscrow(seed),
+        giveAsset: null,
+        giveCollection: null,
+        mintA: null,
+        vault: null,
+        takerAtaA: null,
+        tokenProgramA: null,
+        receiveAsset: null,
+        receiveCollection: null,
+        mintB: null,
+        takerAtaB: null,
+        makerAtaB: null,
+        tokenProgramB: null,
+        coreProgram: MPL_CORE_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...accounts,
+      })
+      .signers([taker])
+      .rpc();
+
+    it("a token for token offer is refused", async () => {
+      const seed = new anchor.BN(9000);
+      await expectError(
+        makeAssetOffer(
+          seed,
+          { token: { mint: mintA, amount: new anchor.BN(1) } },
+          { token: { mint: mintB, amount: new anchor.BN(1) } },
+          {}
+        ),
+        "NoAssetLeg"
+      );
+    });
+
+    it("an asset is held by the escrow and sold for tokens", async () => {
+      const seed = new anchor.BN(9001);
+      const asset = await createAsset(maker);
+      const escrow = deriveAssetEscrow(seed);
+
+      await makeAssetOffer(
+        seed,
+        { asset: { asset } },
+        { token: { mint: mintB, amount: new anchor.BN(50) } },
+        { giveAsset: asset }
+      );
+      expect(await ownerOf(asset)).to.equal(escrow.toBase58());
+
+      const initialMakerB = Number((await provider.connection.getTokenAccountBalance(makerAtaB)).value.amount);
+
+      await takeAssetOffer(seed, {
+        giveAsset: asset,
+        receiveAsset: null,
+        mintB,
+        takerAtaB,
+        makerAtaB,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+      });
+
+      expect(await ownerOf(asset)).to.equal(taker.publicKey.toBase58());
+      const finalMakerB = Number((await provider.connection.getTokenAccountBalance(makerAtaB)).value.amount);
+      expect(finalMakerB - initialMakerB).to.equal(50);
+      expect(await provider.connection.getAccountInfo(escrow)).to.be.null;
+    });
+
+    it("tokens are sold for the taker's asset", async () => {
+      const seed = new anchor.BN(9002);
+      const asset = await createAsset(taker.publicKey);
+      const escrow = deriveAssetEscrow(seed);
+      const assetVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await makeAssetOffer(
+        seed,
+        { token: { mint: mintA, amount: new anchor.BN(10) } },
+        { asset: { asset } },
+        { mintA, makerAtaA, vault: assetVault, tokenProgramA: TOKEN_PROGRAM_ID }
+      );
+
+      const initialTakerA = Number((await provider.connection.getTokenAccountBalance(takerAtaA)).value.amount);
+
+      await takeAssetOffer(seed, {
+        mintA,
+        vault: assetVault,
+        takerAtaA,
+        tokenProgramA: TOKEN_PROGRAM_ID,
+        receiveAsset: asset,
+      });
+
+      expect(await ownerOf(asset)).to.equal(maker.toBase58());
+      const finalTakerA = Number((await provider.connection.getTokenAccountBalance(takerAtaA)).value.amount);
+      expect(finalTakerA - initialTakerA).to.equal(10);
+      expect(await provider.connection.getAccountInfo(assetVault)).to.be.null;
+    });
+
+    it("the maker can take back an escrowed asset", async () => {
+      const seed = new anchor.BN(9003);
+      const asset = await createAsset(maker);
+      const wanted = await createAsset(taker.publicKey);
+      const escrow = deriveAssetEscrow(seed);
+
+      await makeAssetOffer(seed, { asset: { asset } }, { asset: { asset: wanted } }, { giveAsset: asset });
+
+      await program.methods
+        .refundAssetOffer()
+        .accountsStrict({
+          maker,
+          escrow,
+          giveAsset: asset,
+          giveCollection: null,
+          ...noTokenA,
+          coreProgram: MPL_CORE_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect(await ownerOf(asset)).to.equal(maker.toBase58());
+      expect(await ownerOf(wanted)).to.equal(taker.publicKey.toBase58());
+      expect(await provider.connection.getAccountInfo(escrow)).to.be.null;
+    });
+  });
+
+  describe("when a protocol fee is set", async () => {
+    const feeSeed = new anchor.BN(10_000);
+    const referrer = anchor.web3.Keypair.generate();
+    const feeReceive = 10_000;
+
+    const updateConfig = (feeBps: number, referralBps: number) => program.methods
+      .updateConfig(feeBps, referralBps, treasury.publicKey)
+      .accountsStrict({ admin: maker, config: configPda })
+      .rpc();
+
+    const balance = async (address: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(address)).value.amount);
+
+    before(async () => {
+      // 1% fee, a quarter of it to the referrer
+      await updateConfig(100, 2_500);
+
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), feeSeed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, feeReceive);
+
+      await program.methods
+        .make(feeSeed, new anchor.BN(10), new anchor.BN(feeReceive), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    after(async () => {
+      await updateConfig(0, 0);
+    });
+
+    it("only the admin can change the fee", async () => {
+      await expectError(
+        program.methods
+          .updateConfig(10_000, 0, taker.publicKey)
+          .accountsStrict({ admin: taker.publicKey, config: configPda })
+          .signers([taker])
+          .rpc(),
+        "ConstraintHasOne"
+      );
+    });
+
+    it("it refuses fees above the 10% cap", async () => {
+      await expectError(updateConfig(1_001, 0), "InvalidFee");
+    });
+
+    it("a taker cannot name themselves as referrer", async () => {
+      await expectError(
+        program.methods
+          .take(...(await quoteOf(escrowPda)))
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker,
+            mintA,
+            mintB,
+            makerAtaB,
+            takerAtaA,
+            takerAtaB,
+            escrow: escrowPda,
+            vault,
+            offerBook: null,
+            ...feeAccounts(taker.publicKey),
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgramA: TOKEN_PROGRAM_ID,
+            tokenProgramB: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc(),
+        "SelfReferral"
+      );
+    });
+
+    it("it pays the treasury and the referrer before the maker", async () => {
+      const initialMakerB = await balance(makerAtaB);
+      // the offer keeps the 1% it was made at
+      await updateConfig(1_000, 2_500);
+
+      await program.methods
+        .take(...(await quoteOf(escrowPda)))
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker,
+          mintA,
+          mintB,
+          makerAtaB,
+          takerAtaA,
+          takerAtaB,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          ...feeAccounts(referrer.publicKey),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      const { treasuryAtaB, referrerAtaB } = feeAccounts(referrer.publicKey);
+      expect(await balance(makerAtaB) - initialMakerB).to.equal(9_900);
+      expect(await balance(treasuryAtaB)).to.equal(75);
+      expect(await balance(referrerAtaB)).to.equal(25);
+    });
+  });
+
+  describe("when a mint charges a transfer fee and runs a transfer hook", async () => {
+    const hookProgram = anchor.workspace.transferHook as Program<TransferHook>;
+    const hookMint = anchor.web3.Keypair.generate();
+    const feeBps = 100; // 1%
+    const hookSeedA = new anchor.BN(11_000);
+    const hookSeedB = new anchor.BN(11_001);
+
+    const [extraAccountMetaList] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("extra-account-metas"), hookMint.publicKey.toBuffer()],
+      hookProgram.programId
+    );
+    const [counter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), hookMint.publicKey.toBuffer()],
+      hookProgram.programId
+    );
+    const hookAccounts = [
+      { pubkey: hookProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: extraAccountMetaList, isSigner: false, isWritable: false },
+      { pubkey: counter, isSigner: false, isWritable: true },
+    ];
+
+    const hookAta = (owner: anchor.web3.PublicKey) =>
+      getAssociatedTokenAddressSync(hookMint.publicKey, owner, true, TOKEN_2022_PROGRAM_ID);
+    const balance = async (address: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(address)).value.amount);
+    const transfers = async () =>
+      (await hookProgram.account.transferCounter.fetch(counter)).transfers.toNumber();
+    const escrowFor = (seed: anchor.BN) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+    before(async () => {
+      const extensions = [ExtensionType.TransferFeeConfig, ExtensionType.TransferHook];
+      const mintLen = getMintLen(extensions);
+      const mintTx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: maker,
+          newAccountPubkey: hookMint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(hookMint.publicKey, maker, maker, feeBps, BigInt(1_000_000), TOKEN_2022_PROGRAM_ID),
+        createInitializeTransferHookInstruction(hookMint.publicKey, maker, hookProgram.programId, TOKEN_2022_PROGRAM_ID),
+        createInitializeMintInstruction(hookMint.publicKey, 0, maker, null, TOKEN_2022_PROGRAM_ID),
+        createAssociatedTokenAccountInstruction(maker, hookAta(maker), maker, hookMint.publicKey, TOKEN_2022_PROGRAM_ID),
+        createAssociatedTokenAccountInstruction(maker, hookAta(taker.publicKey), taker.publicKey, hookMint.publicKey, TOKEN_2022_PROGRAM_ID),
+      );
+      await provider.sendAndConfirm(mintTx, [hookMint]);
+
+      await hookProgram.methods
+        .initializeExtraAccountMetaList()
+        .accountsStrict({
+          payer: maker,
+          mint: hookMint.publicKey,
+          extraAccountMetaList,
+          counter,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await mintTo(provider.connection, provider.wallet.payer, hookMint.publicKey, hookAta(maker), maker, 1_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+      await mintTo(provider.connection, provider.wallet.payer, hookMint.publicKey, hookAta(taker.publicKey), maker, 2_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, maker, 10);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, 10);
+    });
+
+    it("it only offers what reached the vault after the fee", async () => {
+      const escrow = escrowFor(hookSeedA);
+      const hookVault = hookAta(escrow);
+      const make = () => program.methods
+        .make(hookSeedA, new anchor.BN(1_000), new anchor.BN(10), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA: hookMint.publicKey,
+          mintB,
+          makerAtaA: hookAta(maker),
+          escrow,
+          vault: hookVault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_2022_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        });
+
+      // the hook's counter is an extra account, so the transfer fails without them
+      let failed = false;
+      try {
+        await make().rpc();
+      } catch {
+        failed = true;
+      }
+      expect(failed).to.be.true;
+
+      await make().remainingAccounts(hookAccounts).rpc();
+
+      expect((await program.account.escrow.fetch(escrow)).deposit.toNumber()).to.equal(990);
+      expect(await balance(hookVault)).to.equal(990);
+      expect(await transfers()).to.equal(1);
+    });
+
+    it("it settles and closes a vault holding withheld fees", async () => {
+      const escrow = escrowFor(hookSeedA);
+      const hookVault = hookAta(escrow);
+
+      await program.methods
+        .take(...(await quoteOf(escrow)))
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker,
+          mintA: hookMint.publicKey,
+          mintB,
+          makerAtaB,
+          takerAtaA: hookAta(taker.publicKey),
+          takerAtaB,
+          escrow,
+          vault: hookVault,
+          offerBook: null,
+          ...feeAccounts(),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_2022_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(hookAccounts)
+        .signers([taker])
+        .rpc();
+
+      // 990 less the 1% fee on the way out
+      expect(await balance(hookAta(taker.publicKey))).to.equal(2_000 + 980);
+      expect(await transfers()).to.equal(2);
+      expect(await provider.connection.getAccountInfo(hookVault)).to.be.null;
+      expect(await provider.connection.getAccountInfo(escrow)).to.be.null;
+    });
+
+    it("in net mode the maker receives the full amount after the fee", async () => {
+      const escrow = escrowFor(hookSeedB);
+      const legacyVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await program.methods
+        .make(hookSeedB, new anchor.BN(10), new anchor.BN(1_000), new anchor.BN(0), null, true)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB: hookMint.publicKey,
+          makerAtaA,
+          escrow,
+          vault: legacyVault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_2022_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const initialMakerB = await balance(hookAta(maker));
+      const initialTakerB = await balance(hookAta(taker.publicKey));
+
+      await program.methods
+        .take(...(await quoteOf(escrow)))
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker,
+          mintA,
+          mintB: hookMint.publicKey,
+          makerAtaB: hookAta(maker),
+          takerAtaA,
+          takerAtaB: hookAta(taker.publicKey),
+          escrow,
+          vault: legacyVault,
+          offerBook: null,
+          ...feeAccounts(),
+          treasuryAtaB: hookAta(treasury.publicKey),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_2022_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(hookAccounts)
+        .signers([taker])
+        .rpc();
+
+      // the taker sends 1011, of which 11 is withheld as the fee
+      expect(await balance(hookAta(maker)) - initialMakerB).to.equal(1_000);
+      expect(initialTakerB - await balance(hookAta(taker.publicKey))).to.equal(1_011);
+      expect(await transfers()).to.equal(3);
+    });
+
+    it("it cannot be a basket leg", async () => {
+      const basketSeed = new anchor.BN(11_002);
+      const [basket] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("basket"), maker.toBuffer(), basketSeed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await expectError(
+        program.methods
+          .makeBasket(basketSeed, [{ mint: hookMint.publicKey, amount: new anchor.BN(100) }], [{ mint: mintB, amount: new anchor.BN(10) }])
+          .accountsStrict({
+            maker,
+            basket,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: hookMint.publicKey, isSigner: false, isWritable: false },
+            { pubkey: hookAta(maker), isSigner: false, isWritable: true },
+            { pubkey: hookAta(basket), isSigner: false, isWritable: true },
+            { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
+          ])
+          .rpc(),
+        "UnsupportedMint"
+      );
+    });
+
+    it("it can be sold for a Core asset", async () => {
+      const umi = createUmi(provider.connection.rpcEndpoint).use(mplCore());
+      umi.use(keypairIdentity(umi.eddsa.createKeypairFromSecretKey(provider.wallet.payer.secretKey)));
+      const assetSigner = generateSigner(umi);
+      await create(umi, { asset: assetSigner, name: "Escrowed", uri: "https://example.com/asset.json", owner: pk(taker.publicKey.toBase58()) })
+        .sendAndConfirm(umi);
+      const asset = new anchor.web3.PublicKey(assetSigner.publicKey);
+
+      const seed = new anchor.BN(11_003);
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("asset_escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const assetVault = hookAta(escrow);
+
+      await program.methods
+        .makeAssetOffer(seed, { token: { mint: hookMint.publicKey, amount: new anchor.BN(100) } }, { asset: { asset } })
+        .accountsStrict({
+          maker,
+          escrow,
+          giveAsset: null,
+          giveCollection: null,
+          mintA: hookMint.publicKey,
+          makerAtaA: hookAta(maker),
+          vault: assetVault,
+          tokenProgramA: TOKEN_2022_PROGRAM_ID,
+          coreProgram: MPL_CORE_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(hookAccounts)
+        .rpc();
+
+      // the 1% fee is withheld on the way in, so 99 is on offer
+      const { give } = await program.account.assetEscrow.fetch(escrow);
+      expect(give.token.amount.toNumber()).to.equal(99);
+
+      const initialTakerA = await balance(hookAta(taker.publicKey));
+
+      await program.methods
+        .takeAssetOffer()
+        .accountsStrict({
+          maker,
+          taker: taker.publicKey,
+          escrow,
+          giveAsset: null,
+          giveCollection: null,
+          mintA: hookMint.publicKey,
+          vault: assetVault,
+          takerAtaA: hookAta(taker.publicKey),
+          tokenProgramA: TOKEN_2022_PROGRAM_ID,
+          receiveAsset: asset,
+          receiveCollection: null,
+          mintB: null,
+          takerAtaB: null,
+          makerAtaB: null,
+          tokenProgramB: null,
+          coreProgram: MPL_CORE_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(hookAccounts)
+        .signers([taker])
+        .rpc();
+
+      // 99 less the fee on the way out, and the vault closes despite the withheld fees
+      expect(await balance(hookAta(taker.publicKey)) - initialTakerA).to.equal(98);
+      expect(await provider.connection.getAccountInfo(assetVault)).to.be.null;
+    });
+
+    it("in net mode a counter covers the fee into and out of the counter vault", async () => {
+      const seed = new anchor.BN(11_004);
+      const escrow = escrowFor(seed);
+      const legacyVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+      const [counterOffer] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter"), escrow.toBuffer(), taker.publicKey.toBuffer()],
+        program.programId
+      );
+      const counterVault = hookAta(counterOffer);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, maker, 10);
+      await program.methods
+        .make(seed, new anchor.BN(10), new anchor.BN(1_000), new anchor.BN(0), null, true)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB: hookMint.publicKey,
+          makerAtaA,
+          escrow,
+          vault: legacyVault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_2022_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const initialMakerB = await balance(hookAta(maker));
+      const initialTakerB = await balance(hookAta(taker.publicKey));
+      const now = await provider.connection.getBlockTime(await provider.connection.getSlot());
+
+      await program.methods
+        .counter(new anchor.BN(500), new anchor.BN(now + 60))
+        .accountsStrict({
+          taker: taker.publicKey,
+          mintB: hookMint.publicKey,
+          takerAtaB: hookAta(taker.publicKey),
+          escrow,
+          counterOffer,
+          counterVault,
+          tokenProgramB: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(hookAccounts)
+        .signers([taker])
+        .rpc();
+
+      await program.methods
+        .acceptCounter()
+        .accountsStrict({
+          maker,
+          taker: taker.publicKey,
+          mintA,
+          mintB: hookMint.publicKey,
+          takerAtaA,
+          makerAtaA,
+          makerAtaB: hookAta(maker),
+          escrow,
+          vault: legacyVault,
+          offerBook: null,
+          counterOffer,
+          counterVault,
+          config: configPda,
+          feeRecipient: treasury.publicKey,
+          treasuryAtaB: hookAta(treasury.publicKey),
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(hookAccounts)
+        .rpc();
+
+      // 512 in, 506 reaches the counter vault, 500 reaches the maker
+      expect(initialTakerB - await balance(hookAta(taker.publicKey))).to.equal(512);
+      expect(await balance(hookAta(maker)) - initialMakerB).to.equal(500);
+      expect(await provider.connection.getAccountInfo(counterVault)).to.be.null;
+    });
+  });
+
+  describe("when a taker counters an offer", async () => {
+    const acceptedSeed = new anchor.BN(12_000);
+    const expiringSeed = new anchor.BN(12_001);
+    const refundedSeed = new anchor.BN(12_002);
+    const resizedSeed = new anchor.BN(12_003);
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+    const balance = async (address: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(address)).value.amount);
+    const now = async () => provider.connection.getBlockTime(await provider.connection.getSlot());
+
+    const pdasFor = (seed: anchor.BN) => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [counterOffer] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter"), escrow.toBuffer(), taker.publicKey.toBuffer()],
+        program.programId
+      );
+      return {
+        escrow,
+        vault: getAssociatedTokenAddressSync(mintA, escrow, true),
+        counterOffer,
+        counterVault: getAssociatedTokenAddressSync(mintB, counterOffer, true),
+      };
+    };
+
+    const make = (seed: anchor.BN) => {
+      const { escrow, vault } = pdasFor(seed);
+      return program.methods
+        .make(seed, new anchor.BN(10), new anchor.BN(100), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    };
+
+    const counter = (seed: anchor.BN, proposedReceive: number, expiresAt: number) => {
+      const { escrow, counterOffer, counterVault } = pdasFor(seed);
+      return program.methods
+        .counter(new anchor.BN(proposedReceive), new anchor.BN(expiresAt))
+        .accountsStrict({
+          taker: taker.publicKey,
+          mintB,
+          takerAtaB,
+          escrow,
+          counterOffer,
+          counterVault,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+    };
+
+    const acceptCounter = (seed: anchor.BN) => {
+      const { escrow, vault, counterOffer, counterVault } = pdasFor(seed);
+      const { config, feeRecipient, treasuryAtaB } = feeAccounts();
+      return program.methods
+        .acceptCounter()
+        .accountsStrict({
+          maker,
+          taker: taker.publicKey,
+          mintA,
+          mintB,
+          takerAtaA,
+          makerAtaA,
+          makerAtaB,
+          escrow,
+          vault,
+          offerBook: null,
+          counterOffer,
+          counterVault,
+          config,
+          feeRecipient,
+          treasuryAtaB,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    };
+
+    const updateOffer = (seed: anchor.BN, delta: number) => {
+      const { escrow, vault } = pdasFor(seed);
+      return program.methods
+        .updateOffer(new anchor.BN(100), new anchor.BN(delta))
+        .accountsStrict({
+          maker,
+          mintA,
+          makerAtaA,
+          escrow,
+          vault,
+          offerBook: null,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    };
+
+    const reclaimCounter = (seed: anchor.BN) => {
+      const { escrow, counterOffer, counterVault } = pdasFor(seed);
+      return program.methods
+        .reclaimCounter()
+        .accountsStrict({
+          taker: taker.publicKey,
+          mintB,
+          takerAtaB,
+          counterOffer,
+          escrow,
+          counterVault,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+    };
+
+    before(async () => {
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, maker, 50);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, 300);
+      await make(acceptedSeed);
+      await make(expiringSeed);
+      await make(refundedSeed);
+      await make(resizedSeed);
+    });
+
+    it("a counter must expire in the future", async () => {
+      await expectError(counter(acceptedSeed, 60, (await now()) - 1), "InvalidExpiry");
+    });
+
+    it("the maker can accept a counter, settling both sides at once", async () => {
+      const { escrow, vault, counterOffer, counterVault } = pdasFor(acceptedSeed);
+      const initialTakerA = await balance(takerAtaA);
+      const initialMakerB = await balance(makerAtaB);
+      const initialTakerB = await balance(takerAtaB);
+
+      await counter(acceptedSeed, 60, (await now()) + 60);
+      expect(initialTakerB - await balance(takerAtaB)).to.equal(60);
+      expect((await program.account.counterOffer.fetch(counterOffer)).amount.toNumber()).to.equal(60);
+
+      await acceptCounter(acceptedSeed);
+
+      expect(await balance(takerAtaA) - initialTakerA).to.equal(10);
+      expect(await balance(makerAtaB) - initialMakerB).to.equal(60);
+      for (const closed of [escrow, vault, counterOffer, counterVault]) {
+        expect(await provider.connection.getAccountInfo(closed)).to.be.null;
+      }
+    });
+
+    it("the taker can only reclaim an expired counter, and the maker can no longer accept it", async () => {
+      const { escrow, counterOffer } = pdasFor(expiringSeed);
+      const initialTakerB = await balance(takerAtaB);
+
+      await counter(expiringSeed, 50, (await now()) + 3);
+      await expectError(reclaimCounter(expiringSeed), "CounterNotExpired");
+
+      await sleep(5000);
+      await expectError(acceptCounter(expiringSeed), "CounterExpired");
+
+      await reclaimCounter(expiringSeed);
+
+      expect(await balance(takerAtaB)).to.equal(initialTakerB);
+      expect(await provider.connection.getAccountInfo(counterOffer)).to.be.null;
+      // the offer itself is untouched
+      expect((await program.account.escrow.fetch(escrow)).deposit.toNumber()).to.equal(10);
+    });
+
+    it("the taker can reclaim at once when the offer is withdrawn", async () => {
+      const { escrow, vault, counterOffer } = pdasFor(refundedSeed);
+      const initialTakerB = await balance(takerAtaB);
+
+      await counter(refundedSeed, 40, (await now()) + 60);
+      await program.methods
+        .refund()
+        .accountsStrict({
+          maker,
+          mintA,
+          makerAtaA,
+          escrow,
+          vault,
+          offerBook: null,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await reclaimCounter(refundedSeed);
+
+      expect(await balance(takerAtaB)).to.equal(initialTakerB);
+      expect(await provider.connection.getAccountInfo(counterOffer)).to.be.null;
+    });
+
+    it("the maker cannot shrink the offer under a counter, and gets any top-up back", async () => {
+      const initialTakerA = await balance(takerAtaA);
+      const initialMakerA = await balance(makerAtaA);
+
+      await counter(resizedSeed, 60, (await now()) + 60);
+      expect((await program.account.counterOffer.fetch(pdasFor(resizedSeed).counterOffer)).deposit.toNumber()).to.equal(10);
+
+      await updateOffer(resizedSeed, -9);
+      await expectError(acceptCounter(resizedSeed), "OfferShrunk");
+
+      // 1 left, topped up to 15: the taker gets the 10 they countered for, the maker the other 5
+      await updateOffer(resizedSeed, 14);
+      await acceptCounter(resizedSeed);
+
+      expect(await balance(takerAtaA) - initialTakerA).to.equal(10);
+      expect(initialMakerA - await balance(makerAtaA)).to.equal(0);
+    });
+  });
+
+  describe("when the offer is a Dutch auction", async () => {
+    const auctionSeed = new anchor.BN(13_000);
+
+    const balance = async (address: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(address)).value.amount);
+
+    const takeAccounts = () => ({
+      taker: taker.publicKey,
+      maker,
+      mintA,
+      mintB,
+      makerAtaB,
+      takerAtaA,
+      takerAtaB,
+      escrow: escrowPda,
+      vault,
+      offerBook: null,
+      ...feeAccounts(),
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgramA: TOKEN_PROGRAM_ID,
+      tokenProgramB: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    const takeAuction = (maxPay: number) => program.methods
+      .takeAuction(new anchor.BN(maxPay))
+      .accountsStrict(takeAccounts())
+      .signers([taker])
+      .rpc();
+
+    before(async () => {
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), auctionSeed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, maker, 10);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, 1_000);
+
+      // halfway from 1000 down to 200 when the tests run
+      const now = await provider.connection.getBlockTime(await provider.connection.getSlot());
+      await program.methods
+        .makeAuction(auctionSeed, new anchor.BN(10), {
+          startPrice: new anchor.BN(1_000),
+          floorPrice: new anchor.BN(200),
+          startTs: new anchor.BN(now - 500),
+          endTs: new anchor.BN(now + 500),
+        })
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow: escrowPda,
+          vault,
+          offerBook: null,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("it cannot be taken at the listed price or in fills", async () => {
+      await expectError(
+        program.methods.take(...(await quoteOf(escrowPda))).accountsStrict(takeAccounts()).signers([taker]).rpc(),
+        "AuctionOffer"
+      );
+      await expectError(
+        program.methods.takePartial(new anchor.BN(100), ...(await quoteOf(escrowPda))).accountsStrict(takeAccounts()).signers([taker]).rpc(),
+        "AuctionOffer"
+      );
+    });
+
+    it("it refuses a take when the price is above max_pay", async () => {
+      await expectError(takeAuction(300), "SlippageExceeded");
+    });
+
+    it("it is taken at the decayed price", async () => {
+      const initialMakerB = await balance(makerAtaB);
+      const initialTakerA = await balance(takerAtaA);
+
+      await takeAuction(1_000);
+
+      const paid = await balance(makerAtaB) - initialMakerB;
+      expect(paid).to.be.below(1_000).and.above(200);
+      expect(await balance(takerAtaA) - initialTakerA).to.equal(10);
+      expect(await provider.connection.getAccountInfo(escrowPda)).to.be.null;
+    });
+  });
+
+  describe("when offers are listed in a book", async () => {
+    const seeds = [new anchor.BN(14_000), new anchor.BN(14_001), new anchor.BN(14_002)];
+    const receives = [300, 100, 200];
+
+    const pdasFor = (seed: anchor.BN) => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      return { escrow, vault: getAssociatedTokenAddressSync(mintA, escrow, true) };
+    };
+    const bookPda = () => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("book"), mintA.toBuffer(), mintB.toBuffer()],
+      program.programId
+    )[0];
+    const listed = async () =>
+      (await program.account.offerBook.fetch(bookPda())).entries.map((entry) => entry.escrow.toBase58());
+
+    const makeListed = (seed: anchor.BN, deposit: number, receive: number, evictedEscrow: anchor.web3.PublicKey | null = null) => {
+      const { escrow, vault } = pdasFor(seed);
+      return program.methods
+        .make(seed, new anchor.BN(deposit), new anchor.BN(receive), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA,
+          mintB,
+          makerAtaA,
+          escrow,
+          vault,
+          offerBook: bookPda(),
+          evictedEscrow,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    };
+
+    before(async () => {
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, maker, 30);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, 100);
+
+      for (const [i, seed] of seeds.entries()) {
+        await makeListed(seed, 10, receives[i]);
+      }
+    });
+
+    it("it lists the offers cheapest first", async () => {
+      const [pricey, cheap, mid] = seeds.map((seed) => pdasFor(seed).escrow.toBase58());
+      expect(await listed()).to.deep.equal([cheap, mid, pricey]);
+    });
+
+    it("it needs the book to take a listed offer", async () => {
+      const { escrow, vault } = pdasFor(seeds[1]);
+      await expectError(
+        program.methods
+          .take(...(await quoteOf(escrow)))
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker,
+            mintA,
+            mintB,
+            makerAtaB,
+            takerAtaA,
+            takerAtaB,
+            escrow,
+            vault,
+            offerBook: null,
+            ...feeAccounts(),
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgramA: TOKEN_PROGRAM_ID,
+            tokenProgramB: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc(),
+        "MissingOfferBook"
+      );
+    });
+
+    it("it drops an offer once it is taken", async () => {
+      const { escrow, vault } = pdasFor(seeds[1]);
+      await program.methods
+        .take(...(await quoteOf(escrow)))
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker,
+          mintA,
+          mintB,
+          makerAtaB,
+          takerAtaA,
+          takerAtaB,
+          escrow,
+          vault,
+          offerBook: bookPda(),
+          ...feeAccounts(),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      const [pricey, , mid] = seeds.map((seed) => pdasFor(seed).escrow.toBase58());
+      expect(await listed()).to.deep.equal([mid, pricey]);
+    });
+
+    it("it drops an offer once it is refunded", async () => {
+      const { escrow, vault } = pdasFor(seeds[0]);
+      await program.methods
+        .refund()
+        .accountsStrict({
+          maker,
+          mintA,
+          makerAtaA,
+          escrow,
+          vault,
+          offerBook: bookPda(),
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect(await listed()).to.deep.equal([pdasFor(seeds[2]).escrow.toBase58()]);
+    });
+
+    it("a full book unlists the offer it pushes out", async () => {
+      // 31 more offers fill the book, each pricier than the last
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, maker, 32);
+      const filler = Array.from({ length: 31 }, (_, i) => new anchor.BN(14_100 + i));
+      for (const [i, seed] of filler.entries()) {
+        await makeListed(seed, 1, 1_000 + i);
+      }
+      const priciest = pdasFor(filler[filler.length - 1]).escrow;
+      expect((await listed()).length).to.equal(32);
+
+      const cheapSeed = new anchor.BN(14_200);
+      await expectError(makeListed(cheapSeed, 1, 1), "MissingEvictedEscrow");
+      await makeListed(cheapSeed, 1, 1, priciest);
+
+      expect(await listed()).to.not.include(priciest.toBase58());
+      expect((await program.account.escrow.fetch(priciest)).listed).to.be.false;
+    });
+
+    it("an offer shrunk below one whole token A leaves the book", async () => {
+      // mint A has no decimals, so a 6-decimal mint makes room for dust
+      const payer = provider.wallet.payer;
+      const fineMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+      const fineAta = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, fineMint, maker)).address;
+      await mintTo(provider.connection, payer, fineMint, fineAta, payer, 1_500_000);
+
+      const seed = new anchor.BN(14_300);
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const vault = getAssociatedTokenAddressSync(fineMint, escrow, true);
+      const [offerBook] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("book"), fineMint.toBuffer(), mintB.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .make(seed, new anchor.BN(1_500_000), new anchor.BN(100), new anchor.BN(0), null, false)
+        .accountsStrict({
+          maker,
+          mintA: fineMint,
+          mintB,
+          makerAtaA: fineAta,
+          escrow,
+          vault,
+          offerBook,
+          evictedEscrow: null,
+          config: configPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect((await program.account.offerBook.fetch(offerBook)).entries).to.have.length(1);
+
+      await program.methods
+        .updateOffer(new anchor.BN(100), new anchor.BN(-600_000))
+        .accountsStrict({
+          maker,
+          mintA: fineMint,
+          makerAtaA: fineAta,
+          escrow,
+          vault,
+          offerBook,
+          tokenProgramA: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect((await program.account.offerBook.fetch(offerBook)).entries).to.be.empty;
+      const shrunk = await program.account.escrow.fetch(escrow);
+      expect(shrunk.deposit.toNumber()).to.equal(900_000);
+      expect(shrunk.listed).to.be.false;
+    });
+  });
+
 });
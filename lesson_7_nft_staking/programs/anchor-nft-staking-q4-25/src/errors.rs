@@ -20,4 +20,8 @@ pub enum StakeError {
     AssetAlreadyInitialized,
     #[msg("Not Owner")]
     NotOwner,
+    #[msg("Not Admin")]
+    NotAdmin,
+    #[msg("Overflow")]
+    Overflow,
 }
@@ -12,12 +12,15 @@ pub struct Claim<'info> {
     pub user: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [b"config".as_ref()],
         bump = config.bump,
     )]
     pub config: Account<'info, StakeConfig>,
 
+    // mint_to raises the supply
     #[account(
+        mut,
         seeds = [b"rewards".as_ref(), config.key().as_ref()],
         bump = config.rewards_bump,
     )]
@@ -33,6 +36,7 @@ pub struct Claim<'info> {
     pub user_ata: Account<'info, TokenAccount>,
 
     #[account(
+        mut,
         seeds = [b"user".as_ref(), user.key().as_ref()],
         bump = user_account.bump,
     )]
@@ -45,6 +49,10 @@ pub struct Claim<'info> {
 
 impl<'info> Claim<'info> {
     pub fn claim(&mut self) -> Result<()> {
+        // pick up what the NFTs still staked have earned so far
+        self.config.accrue(Clock::get()?.unix_timestamp)?;
+        self.user_account.settle(self.config.reward_per_share)?;
+
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = MintTo {
             mint: self.reward_mint.to_account_info(),
@@ -52,17 +60,12 @@ impl<'info> Claim<'info> {
             authority: self.config.to_account_info(),
         };
 
-        let config_key = self.config.key();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"rewards",
-            config_key.as_ref(),
-            &[self.config.rewards_bump],
-        ]];
+        // the config PDA is the mint authority
+        let signer_seeds: &[&[&[u8]]] = &[&[b"config", &[self.config.bump]]];
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        let minted_quantity = (self.user_account.points as u64) * (self.reward_mint.decimals as u64);
-
-        mint_to(cpi_context, minted_quantity)?;
+        // points are already reward token base units, so fractions of a token per second pay out too
+        mint_to(cpi_context, self.user_account.points)?;
 
         self.user_account.points = 0;
 
@@ -34,15 +34,18 @@ pub struct InitializeConfig<'info> {
 impl<'info> InitializeConfig<'info> {
     pub fn initialize_config(
         &mut self,
-        points_per_stake: u8,
+        reward_rate: u64,
         max_stake: u8,
         freeze_period: u32,
         bumps: &InitializeConfigBumps,
     ) -> Result<()> {
         self.config.set_inner(StakeConfig {
-            points_per_stake,
+            admin: self.admin.key(),
+            reward_rate,
             max_stake,
             freeze_period,
+            reward_per_share: 0,
+            last_update: Clock::get()?.unix_timestamp,
             rewards_bump: bumps.reward_mint,
             bump: bumps.config,
         });
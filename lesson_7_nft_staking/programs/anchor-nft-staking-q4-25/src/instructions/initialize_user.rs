@@ -24,6 +24,7 @@ impl<'info> Initialize<'info> {
         self.user_account.set_inner(UserAccount {
             points: 0,
             amount_staked: 0,
+            reward_debt: 0,
             bump: bumps.user_account,
         });
 
@@ -4,6 +4,9 @@ pub use initialize_user::*;
 pub mod initialize_config;
 pub use initialize_config::*;
 
+pub mod set_reward_rate;
+pub use set_reward_rate::*;
+
 pub mod create_collection;
 pub use create_collection::*;
 
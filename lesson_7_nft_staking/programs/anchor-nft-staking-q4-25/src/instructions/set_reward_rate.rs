@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::StakeError, state::StakeConfig};
+
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config".as_ref()],
+        bump = config.bump,
+        has_one = admin @ StakeError::NotAdmin,
+    )]
+    pub config: Account<'info, StakeConfig>,
+}
+
+impl<'info> SetRewardRate<'info> {
+    pub fn set_reward_rate(&mut self, reward_rate: u64) -> Result<()> {
+        // close out the old rate first, so only time from here on earns the new one
+        self.config.accrue(Clock::get()?.unix_timestamp)?;
+        self.config.reward_rate = reward_rate;
+
+        Ok(())
+    }
+}
@@ -41,6 +41,7 @@ use crate::{
     pub stake_account: Account<'info, StakeAccount>,
 
     #[account(
+        mut,
         seeds = [b"config".as_ref()],
         bump = config.bump,
     )]
@@ -64,6 +65,10 @@ use crate::{
  impl<'info> Stake<'info> {
      pub fn stake(&mut self, bumps: &StakeBumps) -> Result<()> {
         require!(self.user_account.amount_staked < self.config.max_stake, StakeError::MaxStakeReached);
+
+        let now = Clock::get()?.unix_timestamp;
+        self.config.accrue(now)?;
+        self.user_account.settle(self.config.reward_per_share)?;
         AddPluginV1CpiBuilder::new(&self.core_program.to_account_info())
             .asset(&self.asset.to_account_info())
             .collection(Some(&self.collection.to_account_info()))
@@ -77,11 +82,12 @@ use crate::{
         self.stake_account.set_inner(StakeAccount {
             owner: self.user.key(),
             mint: self.asset.key(),
-            staked_at: Clock::get()?.unix_timestamp,
+            staked_at: now,
             bump: bumps.stake_account,
         });
 
         self.user_account.amount_staked = self.user_account.amount_staked.saturating_add(1);
+        self.user_account.checkpoint(self.config.reward_per_share)?;
 
         Ok(())
      }
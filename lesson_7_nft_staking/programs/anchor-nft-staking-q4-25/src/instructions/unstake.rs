@@ -39,6 +39,7 @@ use crate::{
     pub stake_account: Account<'info, StakeAccount>,
 
     #[account(
+        mut,
         seeds = [b"config".as_ref()],
         bump = config.bump,
     )]
@@ -61,13 +62,14 @@ use crate::{
 
  impl<'info> Unstake<'info> {
      pub fn unstake(&mut self) -> Result<()> {
-        let time_elapsed = Clock::get()?.unix_timestamp - self.stake_account.staked_at;
+        let now = Clock::get()?.unix_timestamp;
+        let time_elapsed = now - self.stake_account.staked_at;
         const SECONDS_IN_DAY: i64 = 24 * 60 * 60;
         let days_elapsed = u32::try_from(time_elapsed / SECONDS_IN_DAY)?;
         require!(days_elapsed >= self.config.freeze_period, StakeError::FreezePeriodNotPassed);
 
-        let points_earned: u32 = days_elapsed * (self.config.points_per_stake as u32);
-        self.user_account.points = self.user_account.points.saturating_add(points_earned);
+        self.config.accrue(now)?;
+        self.user_account.settle(self.config.reward_per_share)?;
 
         let signer_seeds: &[&[&[u8]]] = &[&[
             b"stake",
@@ -95,6 +97,7 @@ use crate::{
             .invoke()?;
 
         self.user_account.amount_staked = self.user_account.amount_staked.saturating_sub(1);
+        self.user_account.checkpoint(self.config.reward_per_share)?;
 
         Ok(())
      }
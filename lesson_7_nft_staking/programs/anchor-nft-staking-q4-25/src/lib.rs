@@ -14,12 +14,16 @@ pub mod anchor_nft_staking_q4_25 {
 
     pub fn initialize_config(
         ctx: Context<InitializeConfig>,
-        points_per_stake: u8,
+        reward_rate: u64,
         max_stake: u8,
         freeze_period: u32,
     ) -> Result<()> {
         ctx.accounts
-            .initialize_config(points_per_stake, max_stake, freeze_period, &ctx.bumps)
+            .initialize_config(reward_rate, max_stake, freeze_period, &ctx.bumps)
+    }
+
+    pub fn set_reward_rate(ctx: Context<SetRewardRate>, reward_rate: u64) -> Result<()> {
+        ctx.accounts.set_reward_rate(reward_rate)
     }
 
     pub fn initialize_user(ctx: Context<Initialize>) -> Result<()> {
@@ -1,11 +1,29 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::StakeError;
+
 #[account]
 #[derive(InitSpace)]
 pub struct StakeConfig {
-    pub points_per_stake: u8,
+    pub admin: Pubkey,
+    pub reward_rate: u64, // reward token base units per staked NFT per second
     pub max_stake: u8,
     pub freeze_period: u32,
+    pub reward_per_share: u128, // base units one NFT has earned since the config was created
+    pub last_update: i64,
     pub rewards_bump: u8,
     pub bump: u8,
 }
+
+impl StakeConfig {
+    /// Brings `reward_per_share` up to `now` at the current rate.
+    /// Call before anything reads it or changes the rate, so each second is paid at the rate in force.
+    pub fn accrue(&mut self, now: i64) -> Result<()> {
+        let elapsed = u128::try_from(now.saturating_sub(self.last_update)).unwrap_or(0);
+        let earned = elapsed.checked_mul(self.reward_rate as u128).ok_or(StakeError::Overflow)?;
+
+        self.reward_per_share = self.reward_per_share.checked_add(earned).ok_or(StakeError::Overflow)?;
+        self.last_update = now;
+        Ok(())
+    }
+}
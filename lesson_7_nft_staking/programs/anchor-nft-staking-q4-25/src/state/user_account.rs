@@ -1,9 +1,37 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::StakeError;
+
 #[account]
 #[derive(InitSpace)]
 pub struct UserAccount {
-    pub points: u32,
+    pub points: u64, // unclaimed reward token base units
     pub amount_staked: u8,
+    pub reward_debt: u128, // amount_staked * reward_per_share at the last checkpoint
     pub bump: u8,
 }
+
+impl UserAccount {
+    /// Credits what the staked NFTs earned since the last checkpoint.
+    /// `reward_per_share` must be freshly accrued.
+    pub fn settle(&mut self, reward_per_share: u128) -> Result<()> {
+        let accrued = self.accrued(reward_per_share)?;
+        let earned = u64::try_from(accrued.saturating_sub(self.reward_debt)).map_err(|_| StakeError::Overflow)?;
+
+        self.points = self.points.checked_add(earned).ok_or(StakeError::Overflow)?;
+        self.reward_debt = accrued;
+        Ok(())
+    }
+
+    /// Starts earning from `reward_per_share` at the current stake; call after `amount_staked` changes.
+    pub fn checkpoint(&mut self, reward_per_share: u128) -> Result<()> {
+        self.reward_debt = self.accrued(reward_per_share)?;
+        Ok(())
+    }
+
+    fn accrued(&self, reward_per_share: u128) -> Result<u128> {
+        Ok((self.amount_staked as u128)
+            .checked_mul(reward_per_share)
+            .ok_or(StakeError::Overflow)?)
+    }
+}
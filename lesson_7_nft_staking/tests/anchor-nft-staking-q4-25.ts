@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { AnchorNftStakingQ425 } from "../target/types/anchor_nft_staking_q4_25";
 import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { getAssociatedTokenAddressSync, getMint } from "@solana/spl-token";
 import { MPL_CORE_PROGRAM_ID } from "@metaplex-foundation/mpl-core";
 import { assert } from "chai";
 
@@ -21,7 +21,7 @@ describe("anchor-nft-staking-q4-25", () => {
   const asset = Keypair.generate();
 
   // Config parameters
-  const pointsPerStake = 10;
+  const rewardRate = 1_500; // base units per staked NFT per second, 0.0015 tokens at 6 decimals
   const maxStake = 5;
   const freezePeriod = 0; // 0 days for testing
 
@@ -38,6 +38,17 @@ describe("anchor-nft-staking-q4-25", () => {
   console.log(`Collection: ${collection.publicKey.toString()}`);
   console.log(`Asset: ${asset.publicKey.toString()}`);
 
+  const stakeAccounts = () => ({
+    user: user.publicKey,
+    asset: asset.publicKey,
+    collection: collection.publicKey,
+    stakeAccount: stakeAccountPda,
+    config: configPda,
+    userAccount: userAccountPda,
+    coreProgram: MPL_CORE_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  });
+
   before(async () => {
     // Airdrop to user
     await connection.requestAirdrop(user.publicKey, 5_000_000_000); // 5 SOL
@@ -84,7 +95,7 @@ describe("anchor-nft-staking-q4-25", () => {
   describe("Initialize Config", () => {
     it("Initialize the staking config", async () => {
       const tx = await program.methods
-        .initializeConfig(pointsPerStake, maxStake, freezePeriod)
+        .initializeConfig(new anchor.BN(rewardRate), maxStake, freezePeriod)
         .accountsStrict({
           admin: admin.publicKey,
           config: configPda,
@@ -97,7 +108,8 @@ describe("anchor-nft-staking-q4-25", () => {
       console.log(`Initialize Config tx: ${tx}`);
 
       const config = await program.account.stakeConfig.fetch(configPda);
-      assert.equal(config.pointsPerStake, pointsPerStake);
+      assert.equal(config.admin.toString(), admin.publicKey.toString());
+      assert.equal(config.rewardRate.toNumber(), rewardRate);
       assert.equal(config.maxStake, maxStake);
       assert.equal(config.freezePeriod, freezePeriod);
       console.log("Config initialized successfully");
@@ -121,7 +133,7 @@ describe("anchor-nft-staking-q4-25", () => {
       const userAccount = await program.account.userAccount.fetch(
         userAccountPda
       );
-      assert.equal(userAccount.points, 0);
+      assert.equal(userAccount.points.toNumber(), 0);
       assert.equal(userAccount.amountStaked, 0);
       console.log("User account initialized successfully");
     });
@@ -271,40 +283,104 @@ describe("anchor-nft-staking-q4-25", () => {
         userAccountPda
       );
       assert.equal(userAccountAfter.amountStaked, 0);
-      // Points should be awarded (time_elapsed * points_per_stake)
-      assert.ok(userAccountAfter.points >= pointsBefore);
+      // Points should be awarded (seconds_staked * reward_rate)
+      assert.ok(userAccountAfter.points.gte(pointsBefore));
       console.log(
-        `NFT unstaked successfully, points earned: ${
-          userAccountAfter.points - pointsBefore
-        }`
+        `NFT unstaked successfully, points earned: ${userAccountAfter.points.sub(
+          pointsBefore
+        )}`
+      );
+    });
+  });
+
+  describe("Reward Rate", () => {
+    it("Only the admin can change the reward rate", async () => {
+      try {
+        await program.methods
+          .setRewardRate(new anchor.BN(1_000))
+          .accountsStrict({ admin: user.publicKey, config: configPda })
+          .signers([user])
+          .rpc();
+        assert.fail("A non-admin should not change the rate");
+      } catch (err) {
+        assert.equal(
+          (err as anchor.AnchorError).error?.errorCode?.code,
+          "NotAdmin"
+        );
+      }
+
+      const config = await program.account.stakeConfig.fetch(configPda);
+      assert.equal(config.rewardRate.toNumber(), rewardRate);
+    });
+
+    it("Keeps what was earned at the old rate after a change", async () => {
+      await program.methods
+        .stake()
+        .accountsStrict(stakeAccounts())
+        .signers([user])
+        .rpc();
+      const stakedAt = await program.account.stakeConfig.fetch(configPda);
+      const pointsBefore = (
+        await program.account.userAccount.fetch(userAccountPda)
+      ).points;
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // stop emissions; what accrued until now must survive
+      await program.methods
+        .setRewardRate(new anchor.BN(0))
+        .accountsStrict({ admin: admin.publicKey, config: configPda })
+        .rpc();
+      const rateChanged = await program.account.stakeConfig.fetch(configPda);
+      const earned = rateChanged.rewardPerShare.sub(stakedAt.rewardPerShare);
+      assert.equal(rateChanged.rewardRate.toNumber(), 0);
+      assert.ok(earned.gten(rewardRate), "At least a second at the old rate");
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.methods
+        .unstake()
+        .accountsStrict(stakeAccounts())
+        .signers([user])
+        .rpc();
+
+      // one NFT staked: the old rate's share, nothing after the change
+      const userAccount = await program.account.userAccount.fetch(
+        userAccountPda
+      );
+      assert.equal(
+        userAccount.points.sub(pointsBefore).toString(),
+        earned.toString()
       );
     });
   });
 
   describe("Claim Rewards", () => {
+    const claimAccounts = () => ({
+      user: user.publicKey,
+      userAta: rewardsAtaPda,
+      config: configPda,
+      userAccount: userAccountPda,
+      rewardMint: rewardMintPda,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    const rewardBalance = async () =>
+      new anchor.BN(
+        (await connection.getTokenAccountBalance(rewardsAtaPda)).value.amount
+      );
+
     it("Claim reward tokens based on points", async () => {
       const userAccountBefore = await program.account.userAccount.fetch(
         userAccountPda
       );
       const pointsBefore = userAccountBefore.points;
-
-      if (pointsBefore === 0) {
-        console.log("No points to claim, skipping claim test");
-        return;
-      }
+      assert.ok(!pointsBefore.isZero(), "Unstaking should have earned points");
 
       const tx = await program.methods
         .claim()
-        .accountsStrict({
-          user: user.publicKey,
-          userAta: rewardsAtaPda,
-          config: configPda,
-          userAccount: userAccountPda,
-          rewardMint: rewardMintPda,
-          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-        })
+        .accountsStrict(claimAccounts())
         .signers([user])
         .rpc();
 
@@ -313,12 +389,64 @@ describe("anchor-nft-staking-q4-25", () => {
       const userAccountAfter = await program.account.userAccount.fetch(
         userAccountPda
       );
-      assert.equal(userAccountAfter.points, 0, "Points should be reset to 0");
+      assert.equal(
+        userAccountAfter.points.toNumber(),
+        0,
+        "Points should be reset to 0"
+      );
 
-      // Check token balance
-      const rewardsAta = await connection.getAccountInfo(rewardsAtaPda);
-      assert.ok(rewardsAta, "Rewards ATA should exist");
+      // Points are base units, minted as they are
+      assert.equal((await rewardBalance()).toString(), pointsBefore.toString());
       console.log("Rewards claimed successfully");
     });
+
+    it("A second claim mints nothing", async () => {
+      const balanceBefore = await rewardBalance();
+
+      await program.methods
+        .claim()
+        .accountsStrict(claimAccounts())
+        .signers([user])
+        .rpc();
+
+      assert.equal((await rewardBalance()).toString(), balanceBefore.toString());
+      const userAccount = await program.account.userAccount.fetch(
+        userAccountPda
+      );
+      assert.equal(userAccount.points.toNumber(), 0);
+    });
+
+    it("Claim pays out while the NFT stays staked", async () => {
+      await program.methods
+        .setRewardRate(new anchor.BN(rewardRate))
+        .accountsStrict({ admin: admin.publicKey, config: configPda })
+        .rpc();
+      await program.methods
+        .stake()
+        .accountsStrict(stakeAccounts())
+        .signers([user])
+        .rpc();
+      const balanceBefore = await rewardBalance();
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+      await program.methods
+        .claim()
+        .accountsStrict(claimAccounts())
+        .signers([user])
+        .rpc();
+
+      const userAccount = await program.account.userAccount.fetch(
+        userAccountPda
+      );
+      assert.equal(userAccount.amountStaked, 1);
+      assert.equal(userAccount.points.toNumber(), 0);
+
+      const rewardMint = await getMint(connection, rewardMintPda);
+      const oneToken = new anchor.BN(10).pow(new anchor.BN(rewardMint.decimals));
+      const claimed = (await rewardBalance()).sub(balanceBefore);
+      assert.ok(claimed.gten(rewardRate), "At least a second's worth");
+      assert.ok(claimed.modrn(rewardRate) === 0, "Whole seconds at the rate");
+      assert.ok(claimed.lt(oneToken), "A fraction of a token per second is paid out");
+    });
   });
 });